@@ -0,0 +1 @@
+//! Sandbox tests for the GitSplits contracts live in `tests/`.
@@ -0,0 +1,180 @@
+//! Drives `generate_chain_signature` → `on_chain_signature` on a local sandbox, with `mock-signer`
+//! standing in for the MPC signer.
+
+use near_workspaces::types::NearToken;
+use near_workspaces::{Account, AccountId, Contract};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+const CODE_HASH: &str = "07a4413609a7b7e6560946a4e99cbc8d3d76f374abfa1f8fbe43d68c67162041";
+
+/// A TDX v4 quote laid out like the `fixtures/attestation` ones, bound to `account_id`.
+fn synthetic_quote(account_id: &AccountId) -> String {
+    let mut quote = vec![0u8; 48 + 584];
+    quote[0..2].copy_from_slice(&4u16.to_le_bytes());
+    quote[4..8].copy_from_slice(&0x81u32.to_le_bytes());
+    let mr_config_id = 48 + 184;
+    quote[mr_config_id] = 0x01;
+    for (i, byte) in (0..CODE_HASH.len()).step_by(2).enumerate() {
+        quote[mr_config_id + 1 + i] = u8::from_str_radix(&CODE_HASH[byte..byte + 2], 16).unwrap();
+    }
+    let report_data = 48 + 520;
+    quote[report_data..report_data + 32].copy_from_slice(&Sha256::digest(account_id.as_bytes()));
+    quote.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn collateral() -> String {
+    json!({
+        "tcb_info_issuer_chain": "tcb-chain",
+        "tcb_info": "tcb-info",
+        "tcb_info_signature": "tcb-signature",
+        "qe_identity_issuer_chain": "qe-chain",
+        "qe_identity": "qe-identity",
+        "qe_identity_signature": "qe-signature",
+    })
+    .to_string()
+}
+
+async fn register_worker(contract: &Contract, worker: &Account) -> anyhow::Result<()> {
+    contract
+        .call("add_allowed_code_hash")
+        .args_json(json!({ "code_hash": CODE_HASH }))
+        .transact()
+        .await?
+        .into_result()?;
+    contract
+        .call("approve_worker")
+        .args_json(json!({ "account_id": worker.id() }))
+        .transact()
+        .await?
+        .into_result()?;
+    worker
+        .call(contract.id(), "register_worker")
+        .args_json(json!({
+            "attestation": { "quote": synthetic_quote(worker.id()), "endorsements": collateral() },
+            "code_hash": CODE_HASH,
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn chain_signature_is_persisted_on_the_transaction() -> anyhow::Result<()> {
+    let sandbox = near_workspaces::sandbox().await?;
+    let signer_wasm = near_workspaces::compile_project("../mock-signer").await?;
+    let contract_wasm = near_workspaces::compile_project("../near").await?;
+
+    let signer = sandbox.dev_deploy(&signer_wasm).await?;
+    signer.call("new").transact().await?.into_result()?;
+    let contract = sandbox.dev_deploy(&contract_wasm).await?;
+    contract.call("new").transact().await?.into_result()?;
+    contract
+        .call("set_mpc_signer")
+        .args_json(json!({ "signer_id": signer.id() }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let root = sandbox.root_account()?;
+    let worker = root.create_subaccount("worker").initial_balance(NearToken::from_near(10)).transact().await?.into_result()?;
+    let owner = root.create_subaccount("owner").initial_balance(NearToken::from_near(10)).transact().await?.into_result()?;
+    register_worker(&contract, &worker).await?;
+    owner
+        .call(contract.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let split_id: String = worker
+        .call(contract.id(), "create_split")
+        .args_json(json!({ "repo_url": "github.com/gitsplits/example", "owner": owner.id() }))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+    let distribution_id: String = owner
+        .call(contract.id(), "record_distribution")
+        .args_json(json!({
+            "split_id": split_id,
+            "amount": "1000",
+            "token_id": null,
+            "transactions": [{
+                "github_username": "alice",
+                "chain_id": "ethereum",
+                "recipient": "0x00000000000000000000000000000000000000a1",
+                "amount": "1000",
+                "tx_hash": null,
+                "status": "Pending",
+                "signature": null,
+            }],
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+
+    let payload = Sha256::digest(b"ethereum transaction").to_vec();
+    let signature: serde_json::Value = worker
+        .call(contract.id(), "generate_chain_signature")
+        .args_json(json!({
+            "distribution_id": distribution_id,
+            "transaction_index": 0,
+            "payload": payload,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(signature["chain_id"], "ethereum");
+    assert_eq!(signature["derivation_path"], format!("gitsplits/{}/ethereum", split_id));
+
+    let distribution: serde_json::Value = contract
+        .view("get_distribution")
+        .args_json(json!({ "distribution_id": distribution_id }))
+        .await?
+        .json()?;
+    assert_eq!(distribution["transactions"][0]["signature"], signature);
+
+    let requests_served: u64 = signer.view("get_requests_served").await?.json()?;
+    assert_eq!(requests_served, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn generate_chain_signature_requires_a_signer_fee() -> anyhow::Result<()> {
+    let sandbox = near_workspaces::sandbox().await?;
+    let contract_wasm = near_workspaces::compile_project("../near").await?;
+    let contract = sandbox.dev_deploy(&contract_wasm).await?;
+    contract.call("new").transact().await?.into_result()?;
+    contract
+        .call("set_mpc_signer")
+        .args_json(json!({ "signer_id": contract.id() }))
+        .transact()
+        .await?
+        .into_result()?;
+    let worker = sandbox
+        .root_account()?
+        .create_subaccount("worker")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await?
+        .into_result()?;
+    register_worker(&contract, &worker).await?;
+
+    let outcome = worker
+        .call(contract.id(), "generate_chain_signature")
+        .args_json(json!({ "distribution_id": "missing", "transaction_index": 0, "payload": vec![0u8; 32] }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", outcome.into_result().unwrap_err()).contains("Attach a deposit"));
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Local stand-in for the NEAR chain signatures MPC signer (`v1.signer`).
+//!
+//! Exposes the same `sign` interface but answers synchronously with a deterministic
+//! response derived from the payload and derivation path, so cross-chain payouts can be
+//! exercised on localnet/testnet without the real MPC network.
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near, near_bindgen, PanicOnDefault};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignRequest {
+    pub payload: [u8; 32],
+    pub path: String,
+    pub key_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureAffinePoint {
+    pub affine_point: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureScalar {
+    pub scalar: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureResponse {
+    pub big_r: SignatureAffinePoint,
+    pub s: SignatureScalar,
+    pub recovery_id: u8,
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct MockSigner {
+    requests_served: u64,
+}
+
+#[near_bindgen]
+impl MockSigner {
+    #[init]
+    pub fn new() -> Self {
+        Self { requests_served: 0 }
+    }
+
+    #[payable]
+    pub fn sign(&mut self, request: SignRequest) -> SignatureResponse {
+        assert!(!env::attached_deposit().is_zero(), "Attach a deposit to cover the signer fee");
+        self.requests_served += 1;
+
+        let mut seed = request.payload.to_vec();
+        seed.extend_from_slice(request.path.as_bytes());
+        seed.extend_from_slice(&request.key_version.to_le_bytes());
+        let r = env::sha256(&seed);
+        let s = env::sha256(&r);
+
+        SignatureResponse {
+            big_r: SignatureAffinePoint {
+                affine_point: format!("02{}", to_hex(&r)).to_uppercase(),
+            },
+            s: SignatureScalar {
+                scalar: to_hex(&s).to_uppercase(),
+            },
+            recovery_id: r[0] & 1,
+        }
+    }
+
+    pub fn get_requests_served(&self) -> u64 {
+        self.requests_served
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,183 @@
+//! Conversion of an amount into exact per-contributor shares.
+//!
+//! Contributor percentages are fractions of `TOTAL_PERCENTAGE`, so an amount rarely divides into
+//! whole yocto-units. `allocate` uses the largest-remainder method:
+//!
+//! 1. every recipient gets `floor(amount * weight / total_weight)`;
+//! 2. the units left over are handed out one each to the recipients with the largest division
+//!    remainders, ties going to the recipient listed first.
+//!
+//! The fractional parts of the exact shares add up to the number of leftover units, and each is
+//! below one, so more recipients have a non-zero remainder than there are leftover units and no
+//! recipient receives more than one extra unit. The shares therefore always sum to `amount` exactly, each
+//! share is within one unit of its exact value, and a zero weight always yields a zero share.
+//! The result depends only on the inputs and their order, so every node computes the same split.
+
+/// Splits `amount` into shares proportional to `weights`, returned in the same order. The shares
+/// sum to exactly `amount`. Panics if the weights sum to zero or overflow.
+pub fn allocate(amount: u128, weights: &[u128]) -> Vec<u128> {
+    let total_weight = weights
+        .iter()
+        .try_fold(0u128, |sum, weight| sum.checked_add(*weight))
+        .expect("Weights overflow");
+    assert!(total_weight > 0, "Weights must not sum to zero");
+
+    let mut shares: Vec<u128> = Vec::with_capacity(weights.len());
+    let mut remainders: Vec<(u128, usize)> = Vec::with_capacity(weights.len());
+    let mut allocated: u128 = 0;
+    for (index, weight) in weights.iter().enumerate() {
+        let (share, remainder) = mul_div_rem(amount, *weight, total_weight);
+        allocated += share;
+        shares.push(share);
+        remainders.push((remainder, index));
+    }
+
+    // Largest remainder first; equal remainders keep their original order.
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let leftover = amount - allocated;
+    for (_, index) in remainders.iter().take(leftover as usize) {
+        shares[*index] += 1;
+    }
+
+    assert_eq!(shares.iter().sum::<u128>(), amount, "Allocation does not cover the amount");
+    shares
+}
+
+/// Computes `value * numerator / denominator` and its remainder with a 256-bit intermediate
+/// product so that yoctoNEAR amounts can be scaled by `TOTAL_PERCENTAGE`-based percentages
+/// without overflow.
+pub fn mul_div_rem(value: u128, numerator: u128, denominator: u128) -> (u128, u128) {
+    assert!(denominator > 0, "Division by zero");
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a1, a0) = (value >> 64, value & MASK);
+    let (b1, b0) = (numerator >> 64, numerator & MASK);
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+    let lo = (p00 & MASK) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    assert!(hi < denominator, "Multiplication overflow");
+
+    let mut remainder = hi;
+    let mut quotient: u128 = 0;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> bit) & 1);
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1 << bit;
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate, mul_div_rem};
+    use crate::TOTAL_PERCENTAGE;
+
+    /// xorshift64*, so the cases are reproducible without extra dependencies
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn next_u128(&mut self) -> u128 {
+            (u128::from(self.next_u64()) << 64) | u128::from(self.next_u64())
+        }
+
+        fn below(&mut self, bound: u64) -> u64 {
+            self.next_u64() % bound
+        }
+    }
+
+    fn assert_exact(amount: u128, weights: &[u128]) -> Vec<u128> {
+        let shares = allocate(amount, weights);
+        let total_weight: u128 = weights.iter().sum();
+        assert_eq!(shares.len(), weights.len());
+        assert_eq!(shares.iter().sum::<u128>(), amount, "amount {} weights {:?}", amount, weights);
+        for (share, weight) in shares.iter().zip(weights) {
+            let (floor, _) = mul_div_rem(amount, *weight, total_weight);
+            assert!(*share == floor || *share == floor + 1, "share {} is not within one unit of {}", share, floor);
+            if *weight == 0 {
+                assert_eq!(*share, 0);
+            }
+        }
+        shares
+    }
+
+    #[test]
+    fn random_allocations_sum_to_amount() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..5_000 {
+            // Spread amounts over every magnitude, from a few units to u128::MAX.
+            let amount = rng.next_u128() >> rng.below(128);
+            let count = 1 + rng.below(200) as usize;
+            let mut weights: Vec<u128> = (0..count)
+                .map(|_| match rng.below(4) {
+                    0 => 0,
+                    1 => u128::from(rng.below(10)),
+                    _ => rng.next_u128() % TOTAL_PERCENTAGE,
+                })
+                .collect();
+            if weights.iter().all(|weight| *weight == 0) {
+                weights[0] = 1;
+            }
+            assert_exact(amount, &weights);
+        }
+    }
+
+    #[test]
+    fn max_amount() {
+        assert_exact(u128::MAX, &[1, 1, 1]);
+        assert_exact(u128::MAX, &[TOTAL_PERCENTAGE / 3, TOTAL_PERCENTAGE / 3, TOTAL_PERCENTAGE / 3 + 1]);
+        assert_eq!(allocate(u128::MAX, &[u128::MAX]), vec![u128::MAX]);
+        assert_eq!(allocate(u128::MAX, &[0, u128::MAX - 1, 1]).iter().sum::<u128>(), u128::MAX);
+    }
+
+    #[test]
+    fn single_unit_goes_to_the_largest_remainder() {
+        assert_eq!(allocate(1, &[1, 1, 1]), vec![1, 0, 0]);
+        assert_eq!(allocate(1, &[1, 3, 2]), vec![0, 1, 0]);
+        assert_eq!(allocate(1, &[0, 0, 5]), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn two_hundred_weights() {
+        let weights = vec![TOTAL_PERCENTAGE / 200; 200];
+        let shares = assert_exact(199, &weights);
+        assert!(shares[..199].iter().all(|share| *share == 1));
+        assert_eq!(shares[199], 0);
+
+        let weights: Vec<u128> = (1..=200).collect();
+        assert_exact(1_000_000_007, &weights);
+        assert_exact(u128::MAX, &weights);
+    }
+
+    #[test]
+    fn equal_remainders_favour_earlier_recipients() {
+        assert_eq!(allocate(2, &[1, 1, 1]), vec![1, 1, 0]);
+        assert_eq!(allocate(5, &[2, 1, 1, 2]), vec![2, 1, 1, 1]);
+        assert_eq!(allocate(10, &[1, 1, 1]), vec![4, 3, 3]);
+    }
+
+    #[test]
+    fn mul_div_rem_matches_native_arithmetic() {
+        let mut rng = Rng(42);
+        for _ in 0..5_000 {
+            let value = u128::from(rng.next_u64());
+            let numerator = u128::from(rng.next_u64());
+            let denominator = 1 + u128::from(rng.next_u64());
+            let product = value * numerator;
+            assert_eq!(mul_div_rem(value, numerator, denominator), (product / denominator, product % denominator));
+        }
+    }
+}
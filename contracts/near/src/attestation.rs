@@ -0,0 +1,199 @@
+//! Parsing and checks for the Intel TDX quotes that workers submit in `register_worker`.
+//!
+//! Workers run inside Phala dstack CVMs. A worker proves which code it runs and which NEAR
+//! account it controls by submitting a TDX DCAP quote (v4) in which:
+//!
+//! - `MRCONFIGID` carries the dstack app compose hash as `0x01 || compose_hash || zero padding`;
+//!   this is the measured code hash that must match the `code_hash` the worker claims.
+//! - the first 32 bytes of `REPORTDATA` are `sha256(worker_account_id)`, binding the quote to the
+//!   account that calls `register_worker` so a quote cannot be replayed by another account.
+//!
+//! The endorsements are the DCAP collateral (TCB info and QE identity with their signatures and
+//! issuer chains) serialized as JSON.
+//!
+//! None of the signatures are checked: neither the quote's signature chain up to Intel's root nor
+//! the collateral signatures are verified, and the collateral is only checked for presence. These
+//! checks catch misconfigured workers and quotes replayed by another account, but a forged quote
+//! passes them, so they are not attestation. `register_worker` additionally requires a code hash
+//! admin to have approved the worker account.
+
+use near_sdk::serde::Deserialize;
+use near_sdk::{env, AccountId};
+
+use crate::Attestation;
+
+const QUOTE_VERSION_4: u16 = 4;
+const TEE_TYPE_TDX: u32 = 0x81;
+const HEADER_LEN: usize = 48;
+const TD_REPORT_LEN: usize = 584;
+const MR_CONFIG_ID_OFFSET: usize = HEADER_LEN + 184;
+const REPORT_DATA_OFFSET: usize = HEADER_LEN + 520;
+const MEASUREMENT_LEN: usize = 48;
+const REPORT_DATA_LEN: usize = 64;
+const COMPOSE_HASH_MARKER: u8 = 0x01;
+
+/// Measurements extracted from a TDX quote
+#[derive(Clone, Debug)]
+pub struct TdxQuote {
+    pub mr_config_id: [u8; MEASUREMENT_LEN],
+    pub report_data: [u8; REPORT_DATA_LEN],
+}
+
+/// DCAP collateral expected in `Attestation.endorsements`
+#[derive(Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QuoteCollateral {
+    pub tcb_info_issuer_chain: String,
+    pub tcb_info: String,
+    pub tcb_info_signature: String,
+    pub qe_identity_issuer_chain: String,
+    pub qe_identity: String,
+    pub qe_identity_signature: String,
+}
+
+impl QuoteCollateral {
+    fn is_complete(&self) -> bool {
+        [
+            &self.tcb_info_issuer_chain,
+            &self.tcb_info,
+            &self.tcb_info_signature,
+            &self.qe_identity_issuer_chain,
+            &self.qe_identity,
+            &self.qe_identity_signature,
+        ]
+        .iter()
+        .all(|field| !field.trim().is_empty())
+    }
+}
+
+impl TdxQuote {
+    /// Parses a hex-encoded (optionally `0x`-prefixed) TDX quote.
+    pub fn parse(quote_hex: &str) -> Result<Self, String> {
+        let bytes = decode_hex(quote_hex).ok_or("Attestation quote is not valid hex")?;
+        if bytes.len() < HEADER_LEN + TD_REPORT_LEN {
+            return Err("Attestation quote is truncated".to_string());
+        }
+
+        let version = u16::from_le_bytes([bytes[0], bytes[1]]);
+        if version != QUOTE_VERSION_4 {
+            return Err(format!("Unsupported attestation quote version {}", version));
+        }
+        let tee_type = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if tee_type != TEE_TYPE_TDX {
+            return Err("Attestation quote is not a TDX quote".to_string());
+        }
+
+        let mut mr_config_id = [0u8; MEASUREMENT_LEN];
+        mr_config_id.copy_from_slice(&bytes[MR_CONFIG_ID_OFFSET..MR_CONFIG_ID_OFFSET + MEASUREMENT_LEN]);
+        let mut report_data = [0u8; REPORT_DATA_LEN];
+        report_data.copy_from_slice(&bytes[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + REPORT_DATA_LEN]);
+
+        Ok(Self {
+            mr_config_id,
+            report_data,
+        })
+    }
+
+    /// The app compose hash measured into `MRCONFIGID`, if the quote carries one.
+    pub fn compose_hash(&self) -> Option<[u8; 32]> {
+        if self.mr_config_id[0] != COMPOSE_HASH_MARKER {
+            return None;
+        }
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&self.mr_config_id[1..33]);
+        Some(hash)
+    }
+}
+
+/// Checks that `attestation` measures `code_hash` and is bound to `account_id`.
+pub fn verify_worker_attestation(
+    attestation: &Attestation,
+    code_hash: &str,
+    account_id: &AccountId,
+) -> Result<(), String> {
+    let collateral: QuoteCollateral = near_sdk::serde_json::from_str(&attestation.endorsements)
+        .map_err(|_| "Attestation endorsements are not valid DCAP collateral".to_string())?;
+    if !collateral.is_complete() {
+        return Err("Attestation endorsements are missing DCAP collateral".to_string());
+    }
+    let quote = TdxQuote::parse(&attestation.quote)?;
+
+    let claimed_hash = decode_hex(&normalize_code_hash(code_hash)).ok_or("Code hash is not valid hex")?;
+    let measured_hash = quote.compose_hash().ok_or("Attestation quote does not measure a compose hash")?;
+    if claimed_hash.as_slice() != measured_hash.as_slice() {
+        return Err("Attested code hash does not match the claimed code hash".to_string());
+    }
+
+    if quote.report_data[..32] != env::sha256(account_id.as_bytes())[..] {
+        return Err("Attestation report data is not bound to the caller".to_string());
+    }
+
+    Ok(())
+}
+
+/// Lowercases a code hash and strips `0x`/`sha256:` prefixes so equivalent spellings compare equal.
+pub fn normalize_code_hash(code_hash: &str) -> String {
+    let trimmed = code_hash.trim();
+    let without_prefix = trimmed
+        .strip_prefix("sha256:")
+        .or_else(|| trimmed.strip_prefix("0x"))
+        .unwrap_or(trimmed);
+    without_prefix.to_ascii_lowercase()
+}
+
+fn decode_hex(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim();
+    let input = input.strip_prefix("0x").unwrap_or(input);
+    if input.len() % 2 == 1 {
+        return None;
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(input.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use near_sdk::serde::Deserialize;
+    use near_sdk::AccountId;
+
+    use super::{normalize_code_hash, verify_worker_attestation};
+    use crate::Attestation;
+
+    #[derive(Deserialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct Fixture {
+        account_id: AccountId,
+        code_hash: String,
+        attestation: Attestation,
+        expected_error: Option<String>,
+    }
+
+    #[test]
+    fn fixtures_produce_their_expected_error() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/attestation");
+        let mut checked = 0;
+        for entry in fs::read_dir(&directory).expect("fixtures directory") {
+            let path = entry.expect("fixture entry").path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let contents = fs::read_to_string(&path).expect("readable fixture");
+            let fixture: Fixture = near_sdk::serde_json::from_str(&contents)
+                .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+
+            let result = verify_worker_attestation(
+                &fixture.attestation,
+                &normalize_code_hash(&fixture.code_hash),
+                &fixture.account_id,
+            );
+            assert_eq!(result.err(), fixture.expected_error, "{}", path.display());
+            checked += 1;
+        }
+        assert!(checked >= 4, "expected at least 4 attestation fixtures, found {}", checked);
+    }
+}
@@ -0,0 +1,172 @@
+//! Withdrawable balances of verified contributors.
+//!
+//! A distribution does not push a transfer to every verified contributor. Each share is credited to
+//! a balance kept per GitHub username and token, and the contributor's verified wallet pulls it
+//! with `withdraw`. Balances follow the GitHub identity, so a contributor who verifies a new wallet
+//! withdraws everything credited before the change. Shares of contributors who have not verified
+//! yet are held as pending distributions and credited to the balance once they verify.
+//! `get_claimable_balances` reports balances, pending distributions and vesting grants together.
+
+use std::collections::BTreeMap;
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, PromiseError};
+
+use crate::events::GitSplitsEvent;
+use crate::{
+    normalize_github_username, GitSplitsContract, GitSplitsContractExt, PauseArea, GAS_FOR_PAYOUT_CALLBACK,
+    NEAR_TOKEN,
+};
+
+/// What a GitHub user is owed in one token
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimableBalance {
+    pub token: String,
+    /// Withdrawable now by the verified wallet
+    pub available: U128,
+    /// Held in pending distributions until they are claimed
+    pub pending: U128,
+    /// Vested in vesting grants and not yet withdrawn with `withdraw_vested`
+    pub vested: U128,
+    /// Still locked in vesting grants
+    pub locked: U128,
+}
+
+/// Running totals of a `ClaimableBalance`
+#[derive(Default)]
+struct ClaimableTotals {
+    available: u128,
+    pending: u128,
+    vested: u128,
+    locked: u128,
+}
+
+#[near_bindgen]
+impl GitSplitsContract {
+    /// Pays `amount` of `token` (NEAR when omitted) from the caller's balance to the caller. The
+    /// caller must be the verified wallet of the GitHub user the balance belongs to. Returns the
+    /// balance left.
+    pub fn withdraw(&mut self, token: Option<AccountId>, amount: U128) -> U128 {
+        self.assert_not_paused(PauseArea::Claims);
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+        let wallet_address = env::predecessor_account_id();
+        let github_username = self
+            .wallet_to_github
+            .get(&wallet_address)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Caller is not a verified wallet"));
+        assert_eq!(
+            self.verified_wallets.get(&github_username),
+            Some(&wallet_address),
+            "Caller is not the verified wallet of {}",
+            github_username
+        );
+
+        let token = token.map(|token| token.to_string()).unwrap_or_else(|| NEAR_TOKEN.to_string());
+        let remaining = self.internal_debit_balance(&github_username, &token, amount.0);
+        let _ = self.internal_transfer(&token, wallet_address.clone(), amount.0).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_PAYOUT_CALLBACK)
+                .on_balance_withdrawn(github_username, wallet_address, token, amount),
+        );
+        U128(remaining)
+    }
+
+    /// Settles a `withdraw` payout, crediting the amount back to the balance if the transfer failed.
+    #[private]
+    pub fn on_balance_withdrawn(
+        &mut self,
+        github_username: String,
+        wallet_address: AccountId,
+        token: String,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        let succeeded = result.is_ok();
+        if succeeded {
+            GitSplitsEvent::BalanceWithdrawn {
+                github_username,
+                wallet_address,
+                token,
+                amount,
+            }
+            .emit();
+        } else {
+            self.internal_credit_balance(&github_username, &token, amount.0);
+            GitSplitsEvent::BalanceWithdrawFailed {
+                github_username,
+                wallet_address,
+                token,
+                amount,
+            }
+            .emit();
+        }
+        succeeded
+    }
+
+    /// Lists, per token, what a GitHub user can withdraw now, what is still held for them in
+    /// pending distributions, and what their vesting grants hold vested or locked.
+    pub fn get_claimable_balances(&self, github_username: String) -> Vec<ClaimableBalance> {
+        let github_username = normalize_github_username(&github_username);
+        let mut balances: BTreeMap<String, ClaimableTotals> = BTreeMap::new();
+        if let Some(credited) = self.claimable_balances.get(&github_username) {
+            for (token, amount) in credited.iter() {
+                balances.entry(token.clone()).or_default().available += *amount;
+            }
+        }
+        for pending in self
+            .pending_records(&github_username)
+            .filter(|pending| pending.funded && !pending.claimed && !pending.expired)
+        {
+            balances.entry(pending.token.clone()).or_default().pending += pending.amount;
+        }
+        for vesting in self.vesting_balances(&github_username) {
+            let totals = balances.entry(vesting.token).or_default();
+            totals.vested += vesting.vested.0 - vesting.withdrawn.0;
+            totals.locked += vesting.locked.0;
+        }
+
+        balances
+            .into_iter()
+            .map(|(token, totals)| ClaimableBalance {
+                token,
+                available: U128(totals.available),
+                pending: U128(totals.pending),
+                vested: U128(totals.vested),
+                locked: U128(totals.locked),
+            })
+            .collect()
+    }
+}
+
+impl GitSplitsContract {
+    /// Adds `amount` of `token` to a GitHub user's balance and returns the new balance.
+    pub(crate) fn internal_credit_balance(&mut self, github_username: &str, token: &str, amount: u128) -> u128 {
+        let balances = self.claimable_balances.entry(github_username.to_string()).or_default();
+        let balance = balances.entry(token.to_string()).or_insert(0);
+        *balance += amount;
+        *balance
+    }
+
+    /// Takes `amount` of `token` from a GitHub user's balance and returns what is left.
+    fn internal_debit_balance(&mut self, github_username: &str, token: &str, amount: u128) -> u128 {
+        let balances = self
+            .claimable_balances
+            .get_mut(github_username)
+            .unwrap_or_else(|| env::panic_str("Nothing to withdraw"));
+        let balance = balances.get(token).copied().unwrap_or(0);
+        assert!(balance >= amount, "Insufficient balance: {} of {} available", balance, token);
+        let remaining = balance - amount;
+        if remaining == 0 {
+            balances.remove(token);
+        } else {
+            balances.insert(token.to_string(), remaining);
+        }
+        if balances.is_empty() {
+            self.claimable_balances.remove(github_username);
+        }
+        remaining
+    }
+}
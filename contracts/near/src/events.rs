@@ -0,0 +1,266 @@
+//! NEP-297 events emitted by the contract under the `gitsplits` standard.
+//!
+//! Every variant carries its own `event_version`; bump it whenever the shape of that variant's
+//! `data` changes so indexers can tell payload schemas apart.
+
+use near_sdk::json_types::U128;
+use near_sdk::{near, AccountId};
+
+use crate::expiry::ExpiryPolicy;
+use crate::{Contributor, DistributionId, PauseArea, Role, SplitId, TransactionStatus, VerificationEntry};
+
+#[near(event_json(standard = "gitsplits"))]
+pub enum GitSplitsEvent {
+    #[event_version("1.0.0")]
+    VerificationStored(VerificationEntry),
+
+    #[event_version("1.0.0")]
+    VerificationUpdated(VerificationEntry),
+
+    #[event_version("1.0.0")]
+    VerificationRevoked(VerificationEntry),
+
+    #[event_version("1.1.0")]
+    SplitCreated {
+        split_id: SplitId,
+        repo_url: String,
+        repo_key: String,
+        owner: AccountId,
+    },
+
+    #[event_version("1.1.0")]
+    SplitUpdated {
+        split_id: SplitId,
+        version: u64,
+        contributors: Vec<Contributor>,
+        reason: Option<String>,
+    },
+
+    #[event_version("1.0.0")]
+    SplitUpdateApproved {
+        split_id: SplitId,
+        contributors_hash: String,
+    },
+
+    #[event_version("1.0.0")]
+    SplitUpdateApprovalCancelled {
+        split_id: SplitId,
+        contributors_hash: String,
+    },
+
+    #[event_version("1.0.0")]
+    SplitOwnershipProposed {
+        split_id: SplitId,
+        proposed_owner: AccountId,
+    },
+
+    #[event_version("1.0.0")]
+    SplitOwnershipProposalCancelled {
+        split_id: SplitId,
+        proposed_owner: AccountId,
+    },
+
+    #[event_version("1.0.0")]
+    SplitOwnershipTransferred {
+        split_id: SplitId,
+        previous_owner: String,
+        owner: AccountId,
+    },
+
+    #[event_version("1.0.0")]
+    WorkerRegistered {
+        account_id: AccountId,
+        code_hash: String,
+    },
+
+    #[event_version("1.0.0")]
+    WorkerApproved { account_id: AccountId },
+
+    #[event_version("1.0.0")]
+    WorkerRemoved { account_id: AccountId },
+
+    #[event_version("1.0.0")]
+    WorkerRegistrationTtlSet { ttl_ns: u64 },
+
+    #[event_version("1.0.0")]
+    CodeHashAdded { code_hash: String },
+
+    #[event_version("1.0.0")]
+    CodeHashRemoved { code_hash: String },
+
+    #[event_version("1.0.0")]
+    TokenAdded { token_id: AccountId },
+
+    #[event_version("1.0.0")]
+    TokenRemoved { token_id: AccountId },
+
+    #[event_version("1.0.0")]
+    MpcSignerSet { signer_id: AccountId },
+
+    #[event_version("1.0.0")]
+    OwnerProposed { owner: AccountId, proposed_owner: AccountId },
+
+    #[event_version("1.0.0")]
+    OwnerProposalCancelled { owner: AccountId, proposed_owner: AccountId },
+
+    #[event_version("1.0.0")]
+    OwnerTransferred { previous_owner: AccountId, owner: AccountId },
+
+    #[event_version("1.0.0")]
+    RoleGranted { role: Role, account_id: AccountId },
+
+    #[event_version("1.0.0")]
+    RoleRevoked { role: Role, account_id: AccountId },
+
+    #[event_version("1.0.0")]
+    PauseChanged { area: PauseArea, paused: bool },
+
+    #[event_version("1.0.0")]
+    StorageDeposited { account_id: AccountId, amount: U128 },
+
+    #[event_version("1.0.0")]
+    StorageWithdrawn { account_id: AccountId, amount: U128 },
+
+    #[event_version("1.0.0")]
+    PendingDistributionStored {
+        id: String,
+        github_username: String,
+        amount: U128,
+        token: String,
+        sponsor: AccountId,
+        split_id: Option<SplitId>,
+    },
+
+    #[event_version("1.0.0")]
+    PendingClaimed {
+        github_username: String,
+        wallet_address: AccountId,
+        amount: U128,
+        token: String,
+        pending_ids: Vec<String>,
+    },
+
+    #[event_version("1.0.0")]
+    VestedWithdrawn {
+        distribution_id: DistributionId,
+        github_username: String,
+        wallet_address: AccountId,
+        amount: U128,
+        token: String,
+    },
+
+    #[event_version("1.0.0")]
+    VestedWithdrawFailed {
+        distribution_id: DistributionId,
+        github_username: String,
+        wallet_address: AccountId,
+        amount: U128,
+        token: String,
+    },
+
+    #[event_version("1.0.0")]
+    ExpiryPolicySet {
+        split_id: Option<SplitId>,
+        policy: Option<ExpiryPolicy>,
+    },
+
+    #[event_version("1.0.0")]
+    PendingExpired {
+        id: String,
+        github_username: String,
+        amount: U128,
+        token: String,
+        split_id: Option<SplitId>,
+        /// Account the funds were sent to; `None` when redistributed or returned to a split treasury
+        receiver_id: Option<AccountId>,
+    },
+
+    #[event_version("1.0.0")]
+    PendingExpiryFailed { id: String, receiver_id: AccountId },
+
+    #[event_version("1.0.0")]
+    TreasuryFunded {
+        split_id: SplitId,
+        token: String,
+        amount: U128,
+        sponsor: AccountId,
+    },
+
+    #[event_version("1.0.0")]
+    DistributionScheduleSet {
+        split_id: SplitId,
+        token: String,
+        amount: U128,
+        interval: u64,
+        next_run_at: u64,
+    },
+
+    #[event_version("1.0.0")]
+    DistributionScheduleRemoved { split_id: SplitId, token: String },
+
+    #[event_version("1.0.0")]
+    ScheduledDistributionExecuted {
+        split_id: SplitId,
+        distribution_id: DistributionId,
+        token: String,
+        amount: U128,
+    },
+
+    #[event_version("1.0.0")]
+    DistributionCreated {
+        distribution_id: DistributionId,
+        split_id: SplitId,
+        amount: U128,
+        token: String,
+        payer: AccountId,
+        recipients: u64,
+    },
+
+    #[event_version("1.0.0")]
+    ChainSignatureRecorded {
+        distribution_id: DistributionId,
+        transaction_index: u32,
+        chain_id: String,
+        derivation_path: String,
+    },
+
+    #[event_version("1.0.0")]
+    BalanceCredited {
+        github_username: String,
+        token: String,
+        amount: U128,
+        distribution_id: DistributionId,
+    },
+
+    #[event_version("1.0.0")]
+    BalanceWithdrawn {
+        github_username: String,
+        wallet_address: AccountId,
+        token: String,
+        amount: U128,
+    },
+
+    #[event_version("1.0.0")]
+    BalanceWithdrawFailed {
+        github_username: String,
+        wallet_address: AccountId,
+        token: String,
+        amount: U128,
+    },
+
+    #[event_version("1.0.0")]
+    FailedPayoutsRetried {
+        distribution_id: DistributionId,
+        transaction_indices: Vec<u32>,
+    },
+
+    #[event_version("1.0.0")]
+    TransactionStatusReported {
+        distribution_id: DistributionId,
+        transaction_index: u32,
+        chain_id: String,
+        status: TransactionStatus,
+        tx_hash: Option<String>,
+        failure_reason: Option<String>,
+    },
+}
@@ -0,0 +1,265 @@
+//! Expiry of unclaimed pending distributions.
+//!
+//! Funds held for a contributor who never verifies would otherwise stay in the contract forever.
+//! An expiry policy says how long a pending distribution may stay unclaimed and where its funds go
+//! afterwards. A split's own policy applies to pending distributions created from that split; the
+//! contract-wide default applies to everything else. Anyone can call `sweep_expired_pending` to
+//! apply the policies to a page of pending distributions.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, PromiseError};
+
+use crate::events::GitSplitsEvent;
+use crate::{
+    allocation, GitSplitsContract, GitSplitsContractExt, PauseArea, PendingDistribution, PendingOrigin, Role,
+    SplitId, TransactionStatus, GAS_FOR_PAYOUT_CALLBACK,
+};
+
+/// Shortest time a pending distribution must stay claimable (1 day)
+pub const MIN_PENDING_EXPIRY_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Where the funds of an expired pending distribution go
+#[derive(
+    near_sdk::borsh::BorshDeserialize, near_sdk::borsh::BorshSerialize, Serialize, Deserialize, Clone, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum ExpiryAction {
+    /// Back to the account that funded it; treasury-funded amounts return to the split treasury
+    RefundSponsor,
+    /// Split among the verified GitHub contributors of the same split by their percentages,
+    /// falling back to a sponsor refund when none of them is verified
+    Redistribute,
+    /// To a fixed account
+    Fallback { account_id: AccountId },
+}
+
+#[derive(
+    near_sdk::borsh::BorshDeserialize, near_sdk::borsh::BorshSerialize, Serialize, Deserialize, Clone, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpiryPolicy {
+    /// Time after creation, in nanoseconds, after which an unclaimed pending distribution expires
+    pub expires_after: u64,
+    pub action: ExpiryAction,
+}
+
+#[near_bindgen]
+impl GitSplitsContract {
+    /// Sets the policy for pending distributions not covered by a split policy, or clears it.
+    pub fn set_default_expiry_policy(&mut self, policy: Option<ExpiryPolicy>) {
+        self.assert_role(Role::Treasurer);
+        if let Some(policy) = policy.as_ref() {
+            assert_valid_policy(policy);
+        }
+        self.default_expiry_policy = policy.clone();
+        GitSplitsEvent::ExpiryPolicySet { split_id: None, policy }.emit();
+    }
+
+    /// Sets or clears the policy for pending distributions created from a split. Only the split
+    /// owner can call this.
+    pub fn set_split_expiry_policy(&mut self, split_id: SplitId, policy: Option<ExpiryPolicy>) {
+        self.assert_not_paused(PauseArea::SplitEdits);
+        self.assert_split_owner(&split_id);
+        let initial_storage_usage = self.storage_checkpoint();
+        match policy.as_ref() {
+            Some(policy) => {
+                assert_valid_policy(policy);
+                self.split_expiry_policies.insert(split_id.clone(), policy.clone());
+            }
+            None => {
+                self.split_expiry_policies.remove(&split_id);
+            }
+        }
+        let split_owner = env::predecessor_account_id();
+        self.charge_storage(&split_owner, initial_storage_usage);
+        GitSplitsEvent::ExpiryPolicySet {
+            split_id: Some(split_id),
+            policy,
+        }
+        .emit();
+    }
+
+    pub fn get_default_expiry_policy(&self) -> Option<&ExpiryPolicy> {
+        self.default_expiry_policy.as_ref()
+    }
+
+    pub fn get_split_expiry_policy(&self, split_id: SplitId) -> Option<&ExpiryPolicy> {
+        self.split_expiry_policies.get(&split_id)
+    }
+
+    /// Applies the expiry policies to a page of pending distributions and returns how many expired.
+    pub fn sweep_expired_pending(&mut self, from_index: Option<u64>, limit: Option<u64>) -> u64 {
+        self.assert_not_paused(PauseArea::Claims);
+        let start = from_index.unwrap_or(0) as usize;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        let now = env::block_timestamp();
+
+        let expired: Vec<(PendingDistribution, ExpiryPolicy)> = self
+            .pending_distributions
+            .values()
+            .skip(start)
+            .take(page_size)
+            .filter(|pending| pending.funded && !pending.claimed && !pending.expired)
+            .filter_map(|pending| {
+                let policy = self.expiry_policy_for(pending)?;
+                (pending.timestamp.saturating_add(policy.expires_after) <= now).then(|| (pending.clone(), policy.clone()))
+            })
+            .collect();
+
+        for (pending, policy) in expired.iter() {
+            if let Some(record) = self.pending_distributions.get_mut(&pending.id) {
+                record.expired = true;
+            }
+            let status = match &policy.action {
+                ExpiryAction::RefundSponsor => {
+                    self.refund_expired_pending(pending);
+                    TransactionStatus::Refunded
+                }
+                ExpiryAction::Redistribute => {
+                    if self.redistribute_expired_pending(pending) {
+                        TransactionStatus::Cancelled
+                    } else {
+                        self.refund_expired_pending(pending);
+                        TransactionStatus::Refunded
+                    }
+                }
+                ExpiryAction::Fallback { account_id } => {
+                    self.pay_expired_pending(pending, account_id.clone());
+                    TransactionStatus::Cancelled
+                }
+            };
+            let failure_reason = Some("Pending distribution expired unclaimed".to_string());
+            self.set_linked_transactions_status(std::slice::from_ref(&pending.id), status, failure_reason);
+        }
+        expired.len() as u64
+    }
+
+    /// Settles the payout of an expired pending distribution. A failed transfer makes the record and
+    /// its distribution transaction pending again so a later sweep or claim can move the funds.
+    #[private]
+    pub fn on_expired_pending_paid(
+        &mut self,
+        pending_id: String,
+        receiver_id: AccountId,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        let succeeded = result.is_ok();
+        if !succeeded {
+            if let Some(pending) = self.pending_distributions.get_mut(&pending_id) {
+                pending.expired = false;
+            }
+            self.set_linked_transactions_status(std::slice::from_ref(&pending_id), TransactionStatus::Pending, None);
+            GitSplitsEvent::PendingExpiryFailed {
+                id: pending_id,
+                receiver_id,
+            }
+            .emit();
+        }
+        succeeded
+    }
+}
+
+impl GitSplitsContract {
+    fn expiry_policy_for(&self, pending: &PendingDistribution) -> Option<&ExpiryPolicy> {
+        pending
+            .split_id
+            .as_ref()
+            .and_then(|split_id| self.split_expiry_policies.get(split_id))
+            .or(self.default_expiry_policy.as_ref())
+    }
+
+    /// Returns expired funds to their sponsor. Amounts paid from a split treasury go back into that
+    /// treasury, which is not necessarily the treasury of the split the contributor belongs to.
+    fn refund_expired_pending(&mut self, pending: &PendingDistribution) {
+        let sponsor: AccountId = pending
+            .sponsor
+            .as_ref()
+            .and_then(|sponsor| sponsor.parse().ok())
+            .unwrap_or_else(|| env::panic_str("Pending distribution has no sponsor"));
+        match pending.treasury_split_id.as_ref() {
+            Some(treasury_split_id) => {
+                self.internal_fund_treasury(treasury_split_id, &pending.token, pending.amount, &sponsor);
+                emit_expired(pending, None);
+            }
+            None => self.pay_expired_pending(pending, sponsor),
+        }
+    }
+
+    fn pay_expired_pending(&mut self, pending: &PendingDistribution, receiver_id: AccountId) {
+        let _ = self.internal_transfer(&pending.token, receiver_id.clone(), pending.amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_PAYOUT_CALLBACK)
+                .on_expired_pending_paid(pending.id.clone(), receiver_id.clone()),
+        );
+        emit_expired(pending, Some(receiver_id));
+    }
+
+    /// Moves expired funds to new pending distributions for the verified contributors of the same
+    /// split and pays them out. Returns `false` when no contributor can receive them.
+    fn redistribute_expired_pending(&mut self, pending: &PendingDistribution) -> bool {
+        let Some(split_id) = pending.split_id.clone() else {
+            return false;
+        };
+        let Some(split) = self.splits.get(&split_id) else {
+            return false;
+        };
+        let recipients: Vec<(String, u128)> = split
+            .contributors
+            .iter()
+            .filter(|contributor| {
+                contributor.split_id.is_none()
+                    && contributor.github_username != pending.github_username
+                    && self.verified_wallets.contains_key(&contributor.github_username)
+            })
+            .map(|contributor| (contributor.github_username.clone(), contributor.percentage))
+            .collect();
+        if recipients.is_empty() {
+            return false;
+        }
+
+        let sponsor: AccountId = pending
+            .sponsor
+            .as_ref()
+            .and_then(|sponsor| sponsor.parse().ok())
+            .unwrap_or_else(env::current_account_id);
+        let weights: Vec<u128> = recipients.iter().map(|(_, percentage)| *percentage).collect();
+        let shares = allocation::allocate(pending.amount, &weights);
+        for ((github_username, _), share) in recipients.iter().zip(shares) {
+            if share > 0 {
+                let origin = PendingOrigin {
+                    split_id: Some(split_id.clone()),
+                    treasury_split_id: pending.treasury_split_id.clone(),
+                    transaction: None,
+                };
+                self.internal_store_pending(github_username, share, &pending.token, &sponsor, origin);
+            }
+        }
+        emit_expired(pending, None);
+        for (github_username, _) in recipients.iter() {
+            self.process_pending_distributions(github_username);
+        }
+        true
+    }
+}
+
+fn assert_valid_policy(policy: &ExpiryPolicy) {
+    assert!(
+        policy.expires_after >= MIN_PENDING_EXPIRY_NS,
+        "Pending distributions must stay claimable for at least a day"
+    );
+}
+
+fn emit_expired(pending: &PendingDistribution, receiver_id: Option<AccountId>) {
+    GitSplitsEvent::PendingExpired {
+        id: pending.id.clone(),
+        github_username: pending.github_username.clone(),
+        amount: U128(pending.amount),
+        token: pending.token.clone(),
+        split_id: pending.split_id.clone(),
+        receiver_id,
+    }
+    .emit();
+}
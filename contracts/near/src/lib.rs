@@ -1,10 +1,30 @@
+mod allocation;
+mod attestation;
+mod balances;
+mod events;
+mod expiry;
+mod repo;
+mod storage;
+mod treasury;
+mod verified_wallets;
+mod vesting;
+
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::{env, near, near_bindgen, AccountId, PanicOnDefault, BorshStorageKey};
-use near_sdk::store::{LookupMap, UnorderedMap, Vector};
-use near_sdk::serde_json::json;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    env, ext_contract, near, near_bindgen, AccountId, BorshStorageKey, Gas, NearToken, PanicOnDefault, Promise,
+    PromiseError, PromiseOrValue,
+};
+use near_sdk::store::{IterableMap, IterableSet, LookupMap, UnorderedMap, Vector};
 use near_sdk::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
-use std::collections::HashSet;
+use events::GitSplitsEvent;
+use expiry::ExpiryPolicy;
+use storage::StorageBalanceRecord;
+use treasury::DistributionSchedule;
+use verified_wallets::VerifiedWallets;
+use vesting::{VestingGrant, VestingSchedule};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 /// Storage keys for collections
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -23,12 +43,69 @@ pub enum StorageKey {
     VerifiedWallets,
     WalletToGithub,
     PendingDistributions,
+    WorkerAccounts,
+    RoleMembers,
+    RoleMembersInner { role: Role },
+    SplitVersions,
+    SplitVersionsInner { split_id: String },
+    SplitUpdateApprovals,
+    PendingSplitOwners,
+    DuplicateSplits,
+    StorageBalances,
+    VestingGrants,
+    GithubVesting,
+    GithubVestingInner { github_username: String },
+    SplitTreasuries,
+    DistributionSchedules,
+    SplitExpiryPolicies,
+    PendingByGithub,
+    PendingByGithubInner { github_username: String },
+    VerifiedWalletsByUsername,
+    ClaimableBalances,
+    AllowedTokens,
+    ApprovedWorkers,
+    RevokedCodeHashes,
+    FundingSplits,
+    VerifiedUsernameStarts,
+    VerifiedUsernameBuckets,
+    PendingRecords,
+    FailedDistributions,
 }
 
 /// Type aliases for better readability
 pub type SplitId = String;
 pub type DistributionId = String;
 
+/// Sum that contributor percentages of a split must add up to (100%)
+pub const TOTAL_PERCENTAGE: u128 = 100_000_000_000_000_000_000_000;
+/// Token identifier used for native NEAR amounts
+pub const NEAR_TOKEN: &str = "NEAR";
+/// Default lifetime of a worker registration before the worker must re-attest (30 days)
+pub const DEFAULT_WORKER_REGISTRATION_TTL_NS: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+/// Gas attached to each outgoing NEP-141 `ft_transfer`
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+/// Gas reserved for the private callbacks that settle payouts
+const GAS_FOR_PAYOUT_CALLBACK: Gas = Gas::from_tgas(10);
+
+/// Gas reserved for the callback that persists an MPC signature
+const GAS_FOR_SIGNATURE_CALLBACK: Gas = Gas::from_tgas(10);
+/// Key version requested from the MPC signer
+const MPC_KEY_VERSION: u32 = 0;
+/// Maximum number of upstream split levels a distribution may pass through
+pub const MAX_SPLIT_DEPTH: u32 = 4;
+
+/// Chain signatures MPC signer interface (e.g. `v1.signer` on mainnet)
+#[ext_contract(ext_signer)]
+pub trait MpcSigner {
+    fn sign(&mut self, request: SignRequest) -> SignatureResponse;
+}
+
+/// NEP-141 interface used to pay out token-denominated distributions
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
 /// Simple attestation structure
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -38,6 +115,63 @@ pub struct Attestation {
     pub endorsements: String,
 }
 
+/// Named roles the owner can grant for privileged operations. The owner implicitly holds every role.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Manages the worker code hash allowlist and worker registrations
+    CodeHashAdmin,
+    /// Pauses and unpauses contract areas
+    Pauser,
+    /// Configures payout infrastructure such as the MPC signer
+    Treasurer,
+    /// Runs state and data migrations
+    Migrator,
+}
+
+/// Contract areas that can be paused independently
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum PauseArea {
+    Verification,
+    SplitEdits,
+    Distributions,
+    Claims,
+}
+
+/// Circuit breaker switches, one per `PauseArea`
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    pub verification: bool,
+    pub split_edits: bool,
+    pub distributions: bool,
+    pub claims: bool,
+}
+
+impl PauseState {
+    pub fn is_paused(&self, area: PauseArea) -> bool {
+        match area {
+            PauseArea::Verification => self.verification,
+            PauseArea::SplitEdits => self.split_edits,
+            PauseArea::Distributions => self.distributions,
+            PauseArea::Claims => self.claims,
+        }
+    }
+
+    fn set(&mut self, area: PauseArea, paused: bool) {
+        match area {
+            PauseArea::Verification => self.verification = paused,
+            PauseArea::SplitEdits => self.split_edits = paused,
+            PauseArea::Distributions => self.distributions = paused,
+            PauseArea::Claims => self.claims = paused,
+        }
+    }
+}
+
 /// Worker information
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -48,11 +182,37 @@ pub struct WorkerInfo {
     pub last_active_at: u64,
 }
 
-/// Contributor structure
+/// Worker liveness as reported by the worker views
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkerStatus {
+    pub account_id: String,
+    pub code_hash: String,
+    pub registered_at: u64,
+    pub last_active_at: u64,
+    pub expires_at: u64,
+    pub active: bool,
+}
+
+/// Contributor structure. A contributor is either a GitHub user or, when `split_id` is set, an
+/// upstream split whose own contributors share the amount allocated to it.
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct Contributor {
+    #[serde(default)]
+    pub github_username: String,
+    pub account_id: Option<String>,
+    pub percentage: u128,
+    #[serde(default)]
+    pub split_id: Option<SplitId>,
+}
+
+/// Contributor layout prior to nested splits, kept for state migration
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ContributorV1 {
     pub github_username: String,
     pub account_id: Option<String>,
     pub percentage: u128,
@@ -64,21 +224,76 @@ pub struct Contributor {
 #[serde(crate = "near_sdk::serde")]
 pub struct Split {
     pub id: SplitId,
+    /// Repository URL as given when the split was created
     pub repo_url: String,
+    /// Canonical repository key the split is looked up by, or `None` for splits created before
+    /// canonical keys until `migrate_repo_keys` processes them
+    pub repo_key: Option<String>,
     pub owner: String,
     pub contributors: Vec<Contributor>,
     pub created_at: u64,
     pub updated_at: u64,
 }
 
-/// Transaction status enum
+/// Split layout prior to nested splits, kept for state migration
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct SplitV1 {
+    pub id: SplitId,
+    pub repo_url: String,
+    pub owner: String,
+    pub contributors: Vec<ContributorV1>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Immutable snapshot of a split's allocation, recorded on every update
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
+pub struct SplitVersion {
+    pub split_id: SplitId,
+    pub version: u64,
+    pub contributors: Vec<Contributor>,
+    pub created_at: u64,
+    pub updated_by: String,
+    pub reason: Option<String>,
+}
+
+/// A split whose repository resolves to the same canonical key as an earlier split
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct DuplicateSplit {
+    pub split_id: SplitId,
+    pub repo_url: String,
+    /// Canonical repository key, or `None` when `repo_url` could not be parsed
+    pub repo_key: Option<String>,
+    /// Split that owns the canonical key, or `None` when `repo_url` could not be parsed
+    pub canonical_split_id: Option<SplitId>,
+}
+
+/// Transaction status enum
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
 pub enum TransactionStatus {
     Pending,
     Completed,
     Failed,
+    /// Broadcast on the target chain, awaiting finality
+    Submitted,
+    /// Returned to the payer instead of reaching the recipient
+    Refunded,
+    /// Abandoned before it was submitted
+    Cancelled,
+}
+
+impl TransactionStatus {
+    /// Whether the transaction has reached an outcome that can no longer change.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Completed | Self::Refunded | Self::Cancelled)
+    }
 }
 
 /// Transaction structure
@@ -86,11 +301,33 @@ pub enum TransactionStatus {
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct Transaction {
+    pub github_username: String,
     pub chain_id: String,
     pub recipient: String,
     pub amount: String,
     pub tx_hash: Option<String>,
     pub status: TransactionStatus,
+    pub signature: Option<ChainSignature>,
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+    /// Block on the target chain that included the transaction
+    #[serde(default)]
+    pub target_block_height: Option<u64>,
+    /// Time of that block, in nanoseconds
+    #[serde(default)]
+    pub target_timestamp: Option<u64>,
+}
+
+/// Outcome of a cross-chain payout as observed by a worker on the target chain
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransactionReport {
+    pub transaction_index: u32,
+    pub status: TransactionStatus,
+    pub tx_hash: Option<String>,
+    pub failure_reason: Option<String>,
+    pub target_block_height: Option<u64>,
+    pub target_timestamp: Option<u64>,
 }
 
 /// Distribution structure
@@ -100,10 +337,46 @@ pub struct Transaction {
 pub struct Distribution {
     pub id: DistributionId,
     pub split_id: SplitId,
+    pub split_version: u64,
     pub amount: u128,
     pub token_id: Option<String>,
     pub timestamp: u64,
+    /// Account that paid the distribution, or that recorded it when paid outside the contract
+    pub payer: String,
     pub transactions: Vec<Transaction>,
+    /// How the amount fanned out through the split and its upstream splits
+    pub fan_out: DistributionNode,
+    /// When set, contributor shares are held as vesting grants instead of being paid out
+    pub vesting: Option<VestingSchedule>,
+    /// Whether the distribution was paid outside the contract and recorded with `record_distribution`
+    pub recorded: bool,
+}
+
+/// Share of a distribution routed through one split. `transactions` indexes the payouts made to
+/// the split's GitHub contributors in `Distribution.transactions`; `children` are the upstream
+/// splits it funded.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionNode {
+    pub split_id: SplitId,
+    pub split_version: u64,
+    pub amount: u128,
+    pub transactions: Vec<u32>,
+    pub children: Vec<DistributionNode>,
+}
+
+/// Working state of a distribution while it fans out through a split graph
+struct DistributionRun {
+    distribution_id: DistributionId,
+    token: String,
+    payer: AccountId,
+    /// Split whose treasury pays for the run, for scheduled runs
+    treasury_split_id: Option<SplitId>,
+    vesting: bool,
+    path: Vec<SplitId>,
+    transactions: Vec<Transaction>,
+    vesting_grants: Vec<VestingGrant>,
 }
 
 /// Pending distribution structure
@@ -117,6 +390,44 @@ pub struct PendingDistribution {
     pub token: String,
     pub timestamp: u64,
     pub claimed: bool,
+    pub sponsor: Option<String>,
+    pub split_id: Option<SplitId>,
+    /// Split treasury the funds came from, where they are refunded on expiry
+    pub treasury_split_id: Option<SplitId>,
+    pub funded: bool,
+    /// Set once an expiry policy has moved the funds elsewhere
+    pub expired: bool,
+    /// Distribution transaction the funds belong to, kept in sync when they are claimed
+    pub transaction: Option<TransactionRef>,
+}
+
+/// Where the funds of a new pending distribution come from
+#[derive(Default)]
+pub(crate) struct PendingOrigin {
+    pub split_id: Option<SplitId>,
+    pub treasury_split_id: Option<SplitId>,
+    pub transaction: Option<TransactionRef>,
+}
+
+/// Position of a transaction within a recorded distribution
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransactionRef {
+    pub distribution_id: DistributionId,
+    pub transaction_index: u32,
+}
+
+/// Pending distribution layout prior to escrow support, kept for state migration
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PendingDistributionV1 {
+    pub id: String,
+    pub github_username: String,
+    pub amount: u128,
+    pub token: String,
+    pub timestamp: u64,
+    pub claimed: bool,
 }
 
 /// Public verification mapping entry
@@ -149,20 +460,71 @@ pub struct RepoVerificationStatus {
     pub unverified: Vec<String>,
 }
 
+/// `msg` payload accepted by `ft_on_transfer`
+#[derive(Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TokenReceiverMessage {
+    Distribute {
+        split_id: Option<SplitId>,
+        repo_url: Option<String>,
+        vesting: Option<VestingSchedule>,
+    },
+    Escrow {
+        github_username: String,
+    },
+    FundTreasury {
+        split_id: SplitId,
+    },
+}
+
 /// Chain signature structure
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct ChainSignature {
-    pub signature: String,
-    pub public_key: String,
+    pub big_r: String,
+    pub s: String,
+    pub recovery_id: u8,
+    pub derivation_path: String,
     pub chain_id: String,
 }
 
+/// Request accepted by the MPC signer's `sign` method
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignRequest {
+    pub payload: [u8; 32],
+    pub path: String,
+    pub key_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureAffinePoint {
+    pub affine_point: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureScalar {
+    pub scalar: String,
+}
+
+/// Response returned by the MPC signer's `sign` method
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureResponse {
+    pub big_r: SignatureAffinePoint,
+    pub s: SignatureScalar,
+    pub recovery_id: u8,
+}
+
 #[near(contract_state)]
 #[derive(PanicOnDefault)]
 pub struct GitSplitsContract {
     registered_workers: LookupMap<AccountId, WorkerInfo>,
+    worker_accounts: IterableSet<AccountId>,
     allowed_code_hashes: Vector<String>,
     splits: UnorderedMap<SplitId, Split>,
     splits_by_repo: LookupMap<String, SplitId>,
@@ -170,20 +532,57 @@ pub struct GitSplitsContract {
     account_github_identities: LookupMap<AccountId, String>,
     distributions: UnorderedMap<DistributionId, Distribution>,
     split_distributions: LookupMap<SplitId, Vector<DistributionId>>,
+    split_versions: LookupMap<SplitId, Vector<SplitVersion>>,
+    split_update_approvals: LookupMap<SplitId, String>,
+    pending_split_owners: LookupMap<SplitId, AccountId>,
+    duplicate_splits: IterableMap<SplitId, DuplicateSplit>,
+    storage_balances: LookupMap<AccountId, StorageBalanceRecord>,
+    vesting_grants: LookupMap<DistributionId, Vec<VestingGrant>>,
+    github_vesting: LookupMap<String, Vector<DistributionId>>,
+    split_treasuries: LookupMap<SplitId, BTreeMap<String, u128>>,
+    distribution_schedules: LookupMap<SplitId, Vec<DistributionSchedule>>,
+    split_expiry_policies: LookupMap<SplitId, ExpiryPolicy>,
     github_to_x_mappings: LookupMap<String, String>,
-    verified_wallets: UnorderedMap<String, AccountId>,
+    /// Verified wallets ordered by normalized GitHub username
+    verified_wallets: VerifiedWallets,
+    /// Verifications of the V2 layout, copied into `verified_wallets` by `migrate_verifications`
+    legacy_verified_wallets: UnorderedMap<String, AccountId>,
     wallet_to_github: LookupMap<AccountId, String>,
     pending_distributions: UnorderedMap<String, PendingDistribution>,
+    pending_by_github: LookupMap<String, Vector<String>>,
+    /// Pending records of the V2 layout, copied into `pending_distributions` by
+    /// `migrate_pending_distributions`
+    legacy_pending_distributions: UnorderedMap<String, PendingDistributionV1>,
+    /// Withdrawable amounts per normalized GitHub username and token
+    claimable_balances: LookupMap<String, BTreeMap<String, u128>>,
+    /// Vesting distributions with payouts that failed and can be retried with `retry_failed`
+    failed_distributions: IterableSet<DistributionId>,
+    /// NEP-141 token contracts accepted by `ft_on_transfer`
+    allowed_tokens: IterableSet<AccountId>,
+    /// Accounts a code hash admin has approved to call `register_worker`
+    approved_workers: IterableSet<AccountId>,
+    /// When each code hash was last removed from the allowlist
+    revoked_code_hashes: LookupMap<String, u64>,
+    /// Splits that list each split as an upstream contributor
+    funding_splits: LookupMap<SplitId, Vec<SplitId>>,
     next_split_nonce: u64,
+    next_distribution_nonce: u64,
+    next_pending_nonce: u64,
+    mpc_signer: Option<AccountId>,
+    default_expiry_policy: Option<ExpiryPolicy>,
+    worker_registration_ttl: u64,
+    role_members: LookupMap<Role, IterableSet<AccountId>>,
+    pending_owner: Option<AccountId>,
+    pause_state: PauseState,
     owner: AccountId,
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
 #[borsh(crate = "near_sdk::borsh")]
-pub struct GitSplitsContractV1 {
+pub struct GitSplitsContractV2 {
     registered_workers: LookupMap<AccountId, WorkerInfo>,
     allowed_code_hashes: Vector<String>,
-    splits: UnorderedMap<SplitId, Split>,
+    splits: UnorderedMap<SplitId, SplitV1>,
     splits_by_repo: LookupMap<String, SplitId>,
     github_identities: LookupMap<String, AccountId>,
     account_github_identities: LookupMap<AccountId, String>,
@@ -191,7 +590,9 @@ pub struct GitSplitsContractV1 {
     split_distributions: LookupMap<SplitId, Vector<DistributionId>>,
     github_to_x_mappings: LookupMap<String, String>,
     verified_wallets: UnorderedMap<String, AccountId>,
-    pending_distributions: UnorderedMap<String, PendingDistribution>,
+    wallet_to_github: LookupMap<AccountId, String>,
+    pending_distributions: UnorderedMap<String, PendingDistributionV1>,
+    next_split_nonce: u64,
     owner: AccountId,
 }
 
@@ -202,6 +603,7 @@ impl GitSplitsContract {
         assert!(!env::state_exists(), "Already initialized");
         Self {
             registered_workers: LookupMap::new(StorageKey::RegisteredWorkers),
+            worker_accounts: IterableSet::new(StorageKey::WorkerAccounts),
             allowed_code_hashes: Vector::new(StorageKey::AllowedCodeHashes),
             splits: UnorderedMap::new(StorageKey::Splits),
             splits_by_repo: LookupMap::new(StorageKey::SplitsByRepo),
@@ -209,68 +611,398 @@ impl GitSplitsContract {
             account_github_identities: LookupMap::new(StorageKey::AccountGithubIdentities),
             distributions: UnorderedMap::new(StorageKey::Distributions),
             split_distributions: LookupMap::new(StorageKey::SplitDistributions),
+            split_versions: LookupMap::new(StorageKey::SplitVersions),
+            split_update_approvals: LookupMap::new(StorageKey::SplitUpdateApprovals),
+            pending_split_owners: LookupMap::new(StorageKey::PendingSplitOwners),
+            duplicate_splits: IterableMap::new(StorageKey::DuplicateSplits),
+            storage_balances: LookupMap::new(StorageKey::StorageBalances),
+            vesting_grants: LookupMap::new(StorageKey::VestingGrants),
+            github_vesting: LookupMap::new(StorageKey::GithubVesting),
+            split_treasuries: LookupMap::new(StorageKey::SplitTreasuries),
+            distribution_schedules: LookupMap::new(StorageKey::DistributionSchedules),
+            split_expiry_policies: LookupMap::new(StorageKey::SplitExpiryPolicies),
             github_to_x_mappings: LookupMap::new(StorageKey::GithubToXMappings),
-            verified_wallets: UnorderedMap::new(StorageKey::VerifiedWallets),
+            verified_wallets: VerifiedWallets::new(
+                StorageKey::VerifiedWalletsByUsername,
+                StorageKey::VerifiedUsernameStarts,
+                StorageKey::VerifiedUsernameBuckets,
+            ),
+            legacy_verified_wallets: UnorderedMap::new(StorageKey::VerifiedWallets),
             wallet_to_github: LookupMap::new(StorageKey::WalletToGithub),
-            pending_distributions: UnorderedMap::new(StorageKey::PendingDistributions),
+            pending_distributions: UnorderedMap::new(StorageKey::PendingRecords),
+            pending_by_github: LookupMap::new(StorageKey::PendingByGithub),
+            legacy_pending_distributions: UnorderedMap::new(StorageKey::PendingDistributions),
+            claimable_balances: LookupMap::new(StorageKey::ClaimableBalances),
+            failed_distributions: IterableSet::new(StorageKey::FailedDistributions),
+            allowed_tokens: IterableSet::new(StorageKey::AllowedTokens),
+            approved_workers: IterableSet::new(StorageKey::ApprovedWorkers),
+            revoked_code_hashes: LookupMap::new(StorageKey::RevokedCodeHashes),
+            funding_splits: LookupMap::new(StorageKey::FundingSplits),
             next_split_nonce: 1,
+            next_distribution_nonce: 1,
+            next_pending_nonce: 1,
+            mpc_signer: None,
+            default_expiry_policy: None,
+            worker_registration_ttl: DEFAULT_WORKER_REGISTRATION_TTL_NS,
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+            pending_owner: None,
+            pause_state: PauseState::default(),
             owner: env::predecessor_account_id(),
         }
     }
 
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let old_state: GitSplitsContractV1 = env::state_read().expect("Old state doesn't exist");
+        let mut old_state: GitSplitsContractV2 = env::state_read().expect("Old state doesn't exist");
+        // The V2 layout predates roles, so only its owner can run this migration.
         assert_eq!(
             env::predecessor_account_id(),
             old_state.owner,
             "Only owner can migrate state"
         );
 
-        let mut wallet_to_github: LookupMap<AccountId, String> = LookupMap::new(StorageKey::WalletToGithub);
-        for (github_username, wallet_address) in old_state.verified_wallets.iter() {
-            wallet_to_github.insert(wallet_address.clone(), github_username.clone());
+        // Contributors gained an optional upstream split; rewrite every split in the new layout.
+        let legacy_splits: Vec<SplitV1> = old_state.splits.values().cloned().collect();
+        old_state.splits.clear();
+        old_state.splits.flush();
+        let mut splits: UnorderedMap<SplitId, Split> = UnorderedMap::new(StorageKey::Splits);
+        for legacy in legacy_splits {
+            splits.insert(legacy.id.clone(), Split {
+                id: legacy.id,
+                repo_url: legacy.repo_url,
+                repo_key: None,
+                owner: legacy.owner,
+                contributors: legacy
+                    .contributors
+                    .into_iter()
+                    .map(|contributor| Contributor {
+                        github_username: contributor.github_username,
+                        account_id: contributor.account_id,
+                        percentage: contributor.percentage,
+                        split_id: None,
+                    })
+                    .collect(),
+                created_at: legacy.created_at,
+                updated_at: legacy.updated_at,
+            });
+        }
+
+        // Snapshot the allocation in force at migration time as version 1 of every configured split.
+        let mut split_versions: LookupMap<SplitId, Vector<SplitVersion>> = LookupMap::new(StorageKey::SplitVersions);
+        for (split_id, split) in splits.iter() {
+            let mut versions = Vector::new(StorageKey::SplitVersionsInner { split_id: split_id.clone() });
+            if !split.contributors.is_empty() {
+                versions.push(SplitVersion {
+                    split_id: split_id.clone(),
+                    version: 1,
+                    contributors: split.contributors.clone(),
+                    created_at: split.updated_at,
+                    updated_by: env::current_account_id().to_string(),
+                    reason: Some("Snapshot taken during state migration".to_string()),
+                });
+            }
+            split_versions.insert(split_id.clone(), versions);
         }
 
         Self {
             registered_workers: old_state.registered_workers,
+            // Workers registered before the index existed are added back on their next call.
+            worker_accounts: IterableSet::new(StorageKey::WorkerAccounts),
             allowed_code_hashes: old_state.allowed_code_hashes,
-            splits: old_state.splits,
+            splits,
             splits_by_repo: old_state.splits_by_repo,
             github_identities: old_state.github_identities,
             account_github_identities: old_state.account_github_identities,
             distributions: old_state.distributions,
             split_distributions: old_state.split_distributions,
+            split_versions,
+            split_update_approvals: LookupMap::new(StorageKey::SplitUpdateApprovals),
+            pending_split_owners: LookupMap::new(StorageKey::PendingSplitOwners),
+            // Repository keys are canonicalized afterwards in batches via `migrate_repo_keys`.
+            duplicate_splits: IterableMap::new(StorageKey::DuplicateSplits),
+            storage_balances: LookupMap::new(StorageKey::StorageBalances),
+            vesting_grants: LookupMap::new(StorageKey::VestingGrants),
+            github_vesting: LookupMap::new(StorageKey::GithubVesting),
+            split_treasuries: LookupMap::new(StorageKey::SplitTreasuries),
+            distribution_schedules: LookupMap::new(StorageKey::DistributionSchedules),
+            split_expiry_policies: LookupMap::new(StorageKey::SplitExpiryPolicies),
             github_to_x_mappings: old_state.github_to_x_mappings,
-            verified_wallets: old_state.verified_wallets,
-            wallet_to_github,
-            pending_distributions: old_state.pending_distributions,
-            next_split_nonce: 1,
+            // Verifications and pending records are moved afterwards in batches via
+            // `migrate_verifications` and `migrate_pending_distributions`.
+            verified_wallets: VerifiedWallets::new(
+                StorageKey::VerifiedWalletsByUsername,
+                StorageKey::VerifiedUsernameStarts,
+                StorageKey::VerifiedUsernameBuckets,
+            ),
+            legacy_verified_wallets: old_state.verified_wallets,
+            wallet_to_github: old_state.wallet_to_github,
+            pending_distributions: UnorderedMap::new(StorageKey::PendingRecords),
+            pending_by_github: LookupMap::new(StorageKey::PendingByGithub),
+            legacy_pending_distributions: old_state.pending_distributions,
+            claimable_balances: LookupMap::new(StorageKey::ClaimableBalances),
+            failed_distributions: IterableSet::new(StorageKey::FailedDistributions),
+            allowed_tokens: IterableSet::new(StorageKey::AllowedTokens),
+            approved_workers: IterableSet::new(StorageKey::ApprovedWorkers),
+            revoked_code_hashes: LookupMap::new(StorageKey::RevokedCodeHashes),
+            funding_splits: LookupMap::new(StorageKey::FundingSplits),
+            next_split_nonce: old_state.next_split_nonce,
+            next_distribution_nonce: 1,
+            next_pending_nonce: 1,
+            mpc_signer: None,
+            default_expiry_policy: None,
+            worker_registration_ttl: DEFAULT_WORKER_REGISTRATION_TTL_NS,
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+            pending_owner: None,
+            // Verification, claims and split edits stay paused until the verification, pending and
+            // repository key batches have run.
+            pause_state: PauseState {
+                verification: true,
+                split_edits: true,
+                claims: true,
+                ..PauseState::default()
+            },
             owner: old_state.owner,
         }
     }
 
-    pub fn add_allowed_code_hash(&mut self, code_hash: String) -> bool {
+    /// Starts a two-step ownership transfer; `new_owner` must call `accept_owner` to complete it.
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        GitSplitsEvent::OwnerProposed {
+            owner: self.owner.clone(),
+            proposed_owner: new_owner.clone(),
+        }
+        .emit();
+        self.pending_owner = Some(new_owner);
+    }
+
+    pub fn cancel_owner_proposal(&mut self) {
+        self.assert_owner();
+        if let Some(proposed_owner) = self.pending_owner.take() {
+            GitSplitsEvent::OwnerProposalCancelled {
+                owner: self.owner.clone(),
+                proposed_owner,
+            }
+            .emit();
+        }
+    }
+
+    pub fn accept_owner(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_owner.as_ref(),
+            Some(&caller),
+            "Only the proposed owner can accept ownership"
+        );
+        GitSplitsEvent::OwnerTransferred {
+            previous_owner: self.owner.clone(),
+            owner: caller.clone(),
+        }
+        .emit();
+        self.owner = caller;
+        self.pending_owner = None;
+    }
+
+    pub fn get_owner(&self) -> &AccountId {
+        &self.owner
+    }
+
+    pub fn get_pending_owner(&self) -> Option<&AccountId> {
+        self.pending_owner.as_ref()
+    }
+
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) -> bool {
+        self.assert_owner();
+        if !self.role_members.contains_key(&role) {
+            self.role_members
+                .insert(role, IterableSet::new(StorageKey::RoleMembersInner { role }));
+        }
+        let granted = self
+            .role_members
+            .get_mut(&role)
+            .map(|members| members.insert(account_id.clone()))
+            .unwrap_or(false);
+        if granted {
+            GitSplitsEvent::RoleGranted { role, account_id }.emit();
+        }
+        granted
+    }
+
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) -> bool {
         self.assert_owner();
-        if self.allowed_code_hashes.iter().any(|hash| hash == &code_hash) {
+        let revoked = self
+            .role_members
+            .get_mut(&role)
+            .map(|members| members.remove(&account_id))
+            .unwrap_or(false);
+        if revoked {
+            GitSplitsEvent::RoleRevoked { role, account_id }.emit();
+        }
+        revoked
+    }
+
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        account_id == self.owner
+            || self
+                .role_members
+                .get(&role)
+                .map(|members| members.contains(&account_id))
+                .unwrap_or(false)
+    }
+
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.role_members
+            .get(&role)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Pauses or resumes one area of the contract.
+    pub fn set_paused(&mut self, area: PauseArea, paused: bool) {
+        self.assert_role(Role::Pauser);
+        self.pause_state.set(area, paused);
+        GitSplitsEvent::PauseChanged { area, paused }.emit();
+    }
+
+    /// Pauses every area at once, e.g. when a worker key is suspected to be compromised.
+    pub fn pause_all(&mut self) {
+        self.assert_role(Role::Pauser);
+        for area in [
+            PauseArea::Verification,
+            PauseArea::SplitEdits,
+            PauseArea::Distributions,
+            PauseArea::Claims,
+        ] {
+            self.pause_state.set(area, true);
+            GitSplitsEvent::PauseChanged { area, paused: true }.emit();
+        }
+    }
+
+    pub fn get_pause_state(&self) -> &PauseState {
+        &self.pause_state
+    }
+
+    pub fn add_allowed_code_hash(&mut self, code_hash: String) -> bool {
+        self.assert_role(Role::CodeHashAdmin);
+        let code_hash = attestation::normalize_code_hash(&code_hash);
+        if self.is_code_hash_allowed(&code_hash) {
             return false;
         }
-        self.allowed_code_hashes.push(code_hash);
+        self.allowed_code_hashes.push(code_hash.clone());
+        GitSplitsEvent::CodeHashAdded { code_hash }.emit();
         true
     }
 
-    pub fn register_worker(&mut self, _attestation: Attestation, code_hash: String) -> bool {
+    /// Removes a code hash from the allowlist. Workers registered with it are rejected from then on,
+    /// even if the hash is allowed again later; they have to register anew.
+    pub fn remove_allowed_code_hash(&mut self, code_hash: String) -> bool {
+        self.assert_role(Role::CodeHashAdmin);
+        let code_hash = attestation::normalize_code_hash(&code_hash);
+        let index = self
+            .allowed_code_hashes
+            .iter()
+            .position(|existing_hash| attestation::normalize_code_hash(existing_hash) == code_hash);
+        match index {
+            Some(index) => {
+                self.allowed_code_hashes.swap_remove(index as u32);
+                self.revoked_code_hashes.insert(code_hash.clone(), env::block_timestamp());
+                GitSplitsEvent::CodeHashRemoved { code_hash }.emit();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_allowed_code_hashes(&self) -> Vec<String> {
+        self.allowed_code_hashes.iter().cloned().collect()
+    }
+
+    /// When `code_hash` was last removed from the allowlist, if ever.
+    pub fn get_code_hash_revoked_at(&self, code_hash: String) -> Option<u64> {
+        self.revoked_code_hashes
+            .get(&attestation::normalize_code_hash(&code_hash))
+            .copied()
+    }
+
+    /// Allows `account_id` to register as a worker. Attestation alone does not admit a worker; see
+    /// `register_worker`.
+    pub fn approve_worker(&mut self, account_id: AccountId) -> bool {
+        self.assert_role(Role::CodeHashAdmin);
+        let approved = self.approved_workers.insert(account_id.clone());
+        if approved {
+            GitSplitsEvent::WorkerApproved { account_id }.emit();
+        }
+        approved
+    }
+
+    pub fn is_worker_approved(&self, account_id: AccountId) -> bool {
+        self.approved_workers.contains(&account_id)
+    }
+
+    /// Removes a worker together with its approval, so it cannot register again until re-approved.
+    pub fn remove_worker(&mut self, account_id: AccountId) -> bool {
+        self.assert_role(Role::CodeHashAdmin);
+        self.approved_workers.remove(&account_id);
+        self.worker_accounts.remove(&account_id);
+        let removed = self.registered_workers.remove(&account_id).is_some();
+        if removed {
+            GitSplitsEvent::WorkerRemoved { account_id }.emit();
+        }
+        removed
+    }
+
+    /// Sets how long a worker registration stays valid before the worker must re-attest.
+    pub fn set_worker_registration_ttl(&mut self, ttl_ns: u64) {
+        self.assert_role(Role::CodeHashAdmin);
+        assert!(ttl_ns > 0, "Registration TTL must be greater than 0");
+        self.worker_registration_ttl = ttl_ns;
+        GitSplitsEvent::WorkerRegistrationTtlSet { ttl_ns }.emit();
+    }
+
+    pub fn get_worker_registration_ttl(&self) -> u64 {
+        self.worker_registration_ttl
+    }
+
+    /// Registers the caller as a worker after checking that `attestation` is a TDX quote measuring
+    /// `code_hash` whose report data is bound to the caller's account.
+    ///
+    /// This is not remote attestation: the quote's signature chain and the DCAP collateral are not
+    /// verified on-chain, so anyone can forge a quote that passes these checks. A worker is only
+    /// admitted once a code hash admin has approved its account with `approve_worker`; code hash
+    /// admins themselves may register directly.
+    pub fn register_worker(&mut self, attestation: Attestation, code_hash: String) -> bool {
         let caller = env::predecessor_account_id();
         let current_timestamp = env::block_timestamp();
+        let code_hash = attestation::normalize_code_hash(&code_hash);
+        if let Err(error) = attestation::verify_worker_attestation(&attestation, &code_hash, &caller) {
+            env::panic_str(&error);
+        }
+        assert!(
+            self.approved_workers.contains(&caller) || self.has_role(Role::CodeHashAdmin, caller.clone()),
+            "Worker {} has not been approved by a code hash admin",
+            caller
+        );
 
         if self.allowed_code_hashes.is_empty() {
-            assert_eq!(caller, self.owner, "Only owner can bootstrap worker code hash");
+            assert!(
+                self.has_role(Role::CodeHashAdmin, caller.clone()),
+                "Only a code hash admin can bootstrap worker code hash"
+            );
             self.allowed_code_hashes.push(code_hash.clone());
+            GitSplitsEvent::CodeHashAdded {
+                code_hash: code_hash.clone(),
+            }
+            .emit();
         }
 
-        let is_allowed = self.allowed_code_hashes.iter().any(|existing_hash| existing_hash == &code_hash);
-        assert!(is_allowed, "Worker code hash is not allowed");
+        assert!(self.is_code_hash_allowed(&code_hash), "Worker code hash is not allowed");
 
+        GitSplitsEvent::WorkerRegistered {
+            account_id: caller.clone(),
+            code_hash: code_hash.clone(),
+        }
+        .emit();
+        self.worker_accounts.insert(caller.clone());
         self.registered_workers.insert(caller, WorkerInfo {
             code_hash,
             registered_at: current_timestamp,
@@ -280,19 +1012,62 @@ impl GitSplitsContract {
         true
     }
 
+    /// Whether `account_id` holds an unexpired registration under a still-allowed code hash.
     pub fn is_worker_registered(&self, account_id: AccountId) -> bool {
-        self.registered_workers.contains_key(&account_id)
+        self.registered_workers
+            .get(&account_id)
+            .map(|worker| self.is_worker_active(worker))
+            .unwrap_or(false)
+    }
+
+    /// Lets a worker report liveness without doing any other work. Returns the recorded timestamp.
+    pub fn heartbeat(&mut self) -> u64 {
+        self.assert_worker_caller();
+        env::block_timestamp()
+    }
+
+    pub fn get_worker(&self, account_id: AccountId) -> Option<WorkerStatus> {
+        self.registered_workers
+            .get(&account_id)
+            .map(|worker| self.worker_status(&account_id, worker))
+    }
+
+    pub fn get_workers(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<WorkerStatus> {
+        let start = from_index.unwrap_or(0) as usize;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        self.worker_accounts
+            .iter()
+            .skip(start)
+            .take(page_size)
+            .filter_map(|account_id| {
+                self.registered_workers
+                    .get(account_id)
+                    .map(|worker| self.worker_status(account_id, worker))
+            })
+            .collect()
+    }
+
+    pub fn get_worker_count(&self) -> u64 {
+        self.worker_accounts.len() as u64
     }
 
     pub fn create_split(&mut self, repo_url: String, owner: AccountId) -> SplitId {
+        self.assert_not_paused(PauseArea::SplitEdits);
         self.assert_worker_caller();
-        assert!(!self.splits_by_repo.contains_key(&repo_url), "Split already exists for this repository");
+        let repo_key = repo::canonical_repo_key(&repo_url).unwrap_or_else(|error| env::panic_str(&error));
+        // Splits not yet re-indexed by `migrate_repo_keys` are still found under their raw URL.
+        assert!(
+            !self.splits_by_repo.contains_key(&repo_key) && self.resolve_repo_split_id(&repo_url).is_none(),
+            "Split already exists for this repository"
+        );
+        let initial_storage_usage = self.storage_checkpoint();
 
         let split_id = format!("split-{}-{}", env::block_height(), self.next_split_nonce);
         self.next_split_nonce = self.next_split_nonce.saturating_add(1);
         let split = Split {
             id: split_id.clone(),
             repo_url: repo_url.clone(),
+            repo_key: Some(repo_key.clone()),
             owner: owner.to_string(),
             contributors: Vec::new(),
             created_at: env::block_timestamp(),
@@ -300,102 +1075,697 @@ impl GitSplitsContract {
         };
 
         self.splits.insert(split_id.clone(), split);
-        self.splits_by_repo.insert(repo_url, split_id.clone());
+        self.splits_by_repo.insert(repo_key.clone(), split_id.clone());
+        GitSplitsEvent::SplitCreated {
+            split_id: split_id.clone(),
+            repo_url,
+            repo_key,
+            owner: owner.clone(),
+        }
+        .emit();
 
         let distributions = Vector::new(StorageKey::SplitDistributionsInner { split_id: split_id.clone() });
         self.split_distributions.insert(split_id.clone(), distributions);
+        let versions = Vector::new(StorageKey::SplitVersionsInner { split_id: split_id.clone() });
+        self.split_versions.insert(split_id.clone(), versions);
+        // The worker creating the split pays for it; the owner has not authorized anything yet.
+        self.charge_storage(&env::predecessor_account_id(), initial_storage_usage);
 
         split_id
     }
 
-    pub fn update_split(&mut self, split_id: SplitId, contributors: Vec<Contributor>) -> bool {
-        self.assert_worker_caller();
-        assert!(!contributors.is_empty(), "Contributors cannot be empty");
-        assert!(contributors.len() <= 200, "Too many contributors");
-
-        if let Some(split) = self.splits.get_mut(&split_id) {
-            let mut seen: HashSet<String> = HashSet::new();
-            let mut normalized_contributors: Vec<Contributor> = Vec::with_capacity(contributors.len());
-            for contributor in contributors.iter() {
-                let normalized_username = normalize_github_username(&contributor.github_username);
-                assert!(!normalized_username.is_empty(), "Contributor username cannot be empty");
-                assert!(contributor.percentage > 0, "Contributor percentage must be > 0");
-                assert!(
-                    seen.insert(normalized_username.clone()),
-                    "Duplicate contributor username"
-                );
-                normalized_contributors.push(Contributor {
-                    github_username: normalized_username,
-                    account_id: contributor.account_id.clone(),
-                    percentage: contributor.percentage,
-                });
-            }
-
-            let total_percentage: u128 = normalized_contributors.iter()
-                .map(|c| c.percentage)
-                .sum();
-
-            assert_eq!(total_percentage, 100_000_000_000_000_000_000_000, "Percentages must add up to 100%");
+    /// Replaces a split's allocation and records it as a new numbered version. `reason` is kept
+    /// with the version so past payouts can be traced back to why the allocation changed.
+    ///
+    /// The split owner may call this directly. A worker may only apply an allocation, including the
+    /// first one of a new split, after the owner approved it exactly via `approve_split_update`.
+    pub fn update_split(&mut self, split_id: SplitId, contributors: Vec<Contributor>, reason: Option<String>) -> bool {
+        self.assert_not_paused(PauseArea::SplitEdits);
+        let split_owner = match self.splits.get(&split_id) {
+            Some(split) => split.owner.clone(),
+            None => return false,
+        };
+        let normalized_contributors = normalize_contributors(&contributors);
+        self.assert_valid_upstream_splits(&split_id, &normalized_contributors);
+
+        if env::predecessor_account_id().as_str() != split_owner {
+            self.assert_worker_caller();
+            let approved_hash = self
+                .split_update_approvals
+                .remove(&split_id)
+                .unwrap_or_else(|| env::panic_str("Split owner has not approved this update"));
+            assert_eq!(
+                approved_hash,
+                contributors_hash(&normalized_contributors),
+                "Update does not match the allocation approved by the split owner"
+            );
+        }
 
-            split.contributors = normalized_contributors;
-            split.updated_at = env::block_timestamp();
-            true
-        } else {
-            false
+        let initial_storage_usage = self.storage_checkpoint();
+        let split = self.splits.get_mut(&split_id).expect("Split not found");
+        let previous_contributors = std::mem::replace(&mut split.contributors, normalized_contributors.clone());
+        split.updated_at = env::block_timestamp();
+        self.update_funding_splits(&split_id, &previous_contributors, &normalized_contributors);
+
+        let versions = self
+            .split_versions
+            .entry(split_id.clone())
+            .or_insert_with(|| Vector::new(StorageKey::SplitVersionsInner { split_id: split_id.clone() }));
+        let version = versions.len() as u64 + 1;
+        versions.push(SplitVersion {
+            split_id: split_id.clone(),
+            version,
+            contributors: normalized_contributors.clone(),
+            created_at: env::block_timestamp(),
+            updated_by: env::predecessor_account_id().to_string(),
+            reason: reason.clone(),
+        });
+        versions.flush();
+        let split_owner: AccountId = split_owner.parse().expect("Invalid split owner");
+        self.charge_storage(&split_owner, initial_storage_usage);
+
+        GitSplitsEvent::SplitUpdated {
+            split_id,
+            version,
+            contributors: normalized_contributors,
+            reason,
         }
+        .emit();
+        true
     }
 
-    pub fn get_split(&self, split_id: SplitId) -> Option<&Split> {
-        self.splits.get(&split_id)
+    /// Approves the allocation a worker may apply with the next `update_split`. `contributors_hash`
+    /// is the value returned by `hash_split_contributors` for the proposed contributors.
+    pub fn approve_split_update(&mut self, split_id: SplitId, contributors_hash: String) {
+        self.assert_not_paused(PauseArea::SplitEdits);
+        self.assert_split_owner(&split_id);
+        let initial_storage_usage = self.storage_checkpoint();
+        self.split_update_approvals.insert(split_id.clone(), contributors_hash.clone());
+        self.charge_storage(&env::predecessor_account_id(), initial_storage_usage);
+        GitSplitsEvent::SplitUpdateApproved {
+            split_id,
+            contributors_hash,
+        }
+        .emit();
     }
 
-    pub fn get_split_by_repo(&self, repo_url: String) -> Option<&Split> {
-        if let Some(split_id) = self.splits_by_repo.get(&repo_url) {
-            self.splits.get(split_id)
-        } else {
-            None
+    pub fn cancel_split_update_approval(&mut self, split_id: SplitId) -> bool {
+        self.assert_split_owner(&split_id);
+        let Some(contributors_hash) = self.split_update_approvals.remove(&split_id) else {
+            return false;
+        };
+        GitSplitsEvent::SplitUpdateApprovalCancelled {
+            split_id,
+            contributors_hash,
         }
+        .emit();
+        true
     }
 
-    pub fn generate_chain_signature(&self, chain_id: String, _tx_data: String) -> ChainSignature {
-        self.assert_worker_caller();
-        ChainSignature {
-            signature: "signature_placeholder".to_string(),
-            public_key: "public_key_placeholder".to_string(),
-            chain_id,
-        }
+    pub fn get_split_update_approval(&self, split_id: SplitId) -> Option<&String> {
+        self.split_update_approvals.get(&split_id)
     }
 
-    pub fn store_verification(&mut self, github_username: String, x_username: String, wallet_address: AccountId) -> bool {
-        self.assert_worker_caller();
-        let normalized_github = normalize_github_username(&github_username);
-        assert!(!normalized_github.is_empty(), "GitHub username cannot be empty");
+    /// Hash of a contributor list after normalization, as expected by `approve_split_update`.
+    pub fn hash_split_contributors(&self, contributors: Vec<Contributor>) -> String {
+        contributors_hash(&normalize_contributors(&contributors))
+    }
 
-        if let Some(previous_wallet) = self.verified_wallets.get(&normalized_github).cloned() {
-            self.wallet_to_github.remove(&previous_wallet);
-        }
-        if let Some(existing_github_for_wallet) = self.wallet_to_github.get(&wallet_address).cloned() {
-            if existing_github_for_wallet != normalized_github {
-                self.verified_wallets.remove(&existing_github_for_wallet);
-                self.github_to_x_mappings.remove(&existing_github_for_wallet);
-            }
+    /// Starts a two-step split ownership handoff; `new_owner` completes it with `accept_split_ownership`.
+    pub fn transfer_split_ownership(&mut self, split_id: SplitId, new_owner: AccountId) {
+        self.assert_not_paused(PauseArea::SplitEdits);
+        self.assert_split_owner(&split_id);
+        self.pending_split_owners.insert(split_id.clone(), new_owner.clone());
+        GitSplitsEvent::SplitOwnershipProposed {
+            split_id,
+            proposed_owner: new_owner,
         }
+        .emit();
+    }
 
-        self.github_to_x_mappings.insert(normalized_github.clone(), x_username);
-        self.verified_wallets.insert(normalized_github.clone(), wallet_address.clone());
-        self.wallet_to_github.insert(wallet_address.clone(), normalized_github.clone());
-
-        let event = VerificationEntry {
-            github_username: normalized_github.clone(),
-            wallet_address: wallet_address.to_string(),
-            x_username: self.github_to_x_mappings.get(&normalized_github).cloned(),
+    pub fn cancel_split_ownership_transfer(&mut self, split_id: SplitId) -> bool {
+        self.assert_split_owner(&split_id);
+        let Some(proposed_owner) = self.pending_split_owners.remove(&split_id) else {
+            return false;
         };
-        emit_verification_event("verification_stored", &event);
-        self.process_pending_distributions(&normalized_github);
+        GitSplitsEvent::SplitOwnershipProposalCancelled {
+            split_id,
+            proposed_owner,
+        }
+        .emit();
+        true
+    }
+
+    pub fn accept_split_ownership(&mut self, split_id: SplitId) {
+        self.assert_not_paused(PauseArea::SplitEdits);
+        let caller = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_split_owners.get(&split_id),
+            Some(&caller),
+            "Only the proposed owner can accept split ownership"
+        );
+        self.pending_split_owners.remove(&split_id);
+        // An approval granted by the previous owner must not outlive the handoff.
+        self.split_update_approvals.remove(&split_id);
+
+        let split = self.splits.get_mut(&split_id).expect("Split not found");
+        let previous_owner = std::mem::replace(&mut split.owner, caller.to_string());
+        GitSplitsEvent::SplitOwnershipTransferred {
+            split_id,
+            previous_owner,
+            owner: caller,
+        }
+        .emit();
+    }
+
+    pub fn get_pending_split_owner(&self, split_id: SplitId) -> Option<&AccountId> {
+        self.pending_split_owners.get(&split_id)
+    }
+
+    pub fn get_split(&self, split_id: SplitId) -> Option<&Split> {
+        self.splits.get(&split_id)
+    }
+
+    pub fn get_split_version(&self, split_id: SplitId, version: u64) -> Option<&SplitVersion> {
+        if version == 0 {
+            return None;
+        }
+        self.split_versions
+            .get(&split_id)
+            .and_then(|versions| versions.get((version - 1) as u32))
+    }
+
+    /// Lists versions of a split in ascending order, starting at version `from` (default 1).
+    pub fn get_split_versions(&self, split_id: SplitId, from: Option<u64>, limit: Option<u64>) -> Vec<SplitVersion> {
+        let start = from.unwrap_or(1).max(1) - 1;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        self.split_versions
+            .get(&split_id)
+            .map(|versions| {
+                versions
+                    .iter()
+                    .skip(start as usize)
+                    .take(page_size)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_split_version_count(&self, split_id: SplitId) -> u64 {
+        self.split_versions
+            .get(&split_id)
+            .map(|versions| versions.len() as u64)
+            .unwrap_or(0)
+    }
+
+    pub fn get_split_by_repo(&self, repo_url: String) -> Option<&Split> {
+        if let Some(split_id) = self.resolve_repo_split_id(&repo_url) {
+            self.splits.get(split_id)
+        } else {
+            None
+        }
+    }
+
+    /// Re-indexes existing splits under canonical repository keys, `limit` splits at a time.
+    /// Splits whose repository already belongs to another split, or whose URL cannot be parsed,
+    /// are flagged in `get_duplicate_splits` instead of being re-indexed. Returns the number of
+    /// splits processed; safe to re-run over the same range.
+    pub fn migrate_repo_keys(&mut self, from_index: u64, limit: u64) -> u64 {
+        self.assert_role(Role::Migrator);
+        let splits: Vec<(SplitId, String)> = self
+            .splits
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(split_id, split)| (split_id.clone(), split.repo_url.clone()))
+            .collect();
+
+        for (split_id, repo_url) in splits.iter() {
+            let repo_key = match repo::canonical_repo_key(repo_url) {
+                Ok(repo_key) => repo_key,
+                Err(_) => {
+                    self.duplicate_splits.insert(split_id.clone(), DuplicateSplit {
+                        split_id: split_id.clone(),
+                        repo_url: repo_url.clone(),
+                        repo_key: None,
+                        canonical_split_id: None,
+                    });
+                    continue;
+                }
+            };
+
+            if let Some(split) = self.splits.get_mut(split_id) {
+                split.repo_key = Some(repo_key.clone());
+            }
+            match self.splits_by_repo.get(&repo_key).cloned() {
+                Some(existing_split_id) if &existing_split_id != split_id => {
+                    self.duplicate_splits.insert(split_id.clone(), DuplicateSplit {
+                        split_id: split_id.clone(),
+                        repo_url: repo_url.clone(),
+                        repo_key: Some(repo_key.clone()),
+                        canonical_split_id: Some(existing_split_id),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    self.splits_by_repo.insert(repo_key.clone(), split_id.clone());
+                }
+            }
+            if &repo_key != repo_url && self.splits_by_repo.get(repo_url) == Some(split_id) {
+                self.splits_by_repo.remove(repo_url);
+            }
+        }
+        splits.len() as u64
+    }
+
+    /// Copies verifications of the V2 layout into the ordered verification index, `limit` at a time.
+    /// Usernames verified again since the migration keep their newer wallet. Returns the number of
+    /// verifications processed; safe to re-run over the same range.
+    pub fn migrate_verifications(&mut self, from_index: u64, limit: u64) -> u64 {
+        self.assert_role(Role::Migrator);
+        let verifications: Vec<(String, AccountId)> = self
+            .legacy_verified_wallets
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(github_username, wallet_address)| (github_username.clone(), wallet_address.clone()))
+            .collect();
+
+        for (github_username, wallet_address) in verifications.iter() {
+            let github_username = normalize_github_username(github_username);
+            if !self.verified_wallets.contains_key(&github_username) {
+                self.verified_wallets.insert(github_username, wallet_address.clone());
+            }
+        }
+        verifications.len() as u64
+    }
+
+    /// Copies pending records of the V2 layout into the current layout, `limit` at a time. They were
+    /// never backed by funds held in the contract, so they are carried over as unfunded. Returns the
+    /// number of records processed; safe to re-run over the same range.
+    pub fn migrate_pending_distributions(&mut self, from_index: u64, limit: u64) -> u64 {
+        self.assert_role(Role::Migrator);
+        let legacy_pending: Vec<PendingDistributionV1> = self
+            .legacy_pending_distributions
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+
+        for legacy in legacy_pending.iter() {
+            if self.pending_distributions.contains_key(&legacy.id) {
+                continue;
+            }
+            let github_username = normalize_github_username(&legacy.github_username);
+            self.pending_by_github
+                .entry(github_username.clone())
+                .or_insert_with(|| Vector::new(StorageKey::PendingByGithubInner { github_username }))
+                .push(legacy.id.clone());
+            self.pending_distributions.insert(legacy.id.clone(), PendingDistribution {
+                id: legacy.id.clone(),
+                github_username: legacy.github_username.clone(),
+                amount: legacy.amount,
+                token: legacy.token.clone(),
+                timestamp: legacy.timestamp,
+                claimed: legacy.claimed,
+                sponsor: None,
+                split_id: None,
+                treasury_split_id: None,
+                funded: false,
+                expired: false,
+                transaction: None,
+            });
+        }
+        legacy_pending.len() as u64
+    }
+
+    pub fn get_duplicate_splits(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<DuplicateSplit> {
+        let start = from_index.unwrap_or(0) as usize;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        self.duplicate_splits
+            .values()
+            .skip(start)
+            .take(page_size)
+            .cloned()
+            .collect()
+    }
+
+    /// Splits the attached NEAR between the split's contributors. Shares of verified contributors are
+    /// credited to their balance, withdrawn with `withdraw`; shares of unverified contributors are
+    /// held by the contract as pending distributions.
+    /// With a `vesting` schedule every share is instead held as a grant withdrawn via `withdraw_vested`.
+    /// The caller pays for the storage the distribution takes and must hold a storage balance.
+    #[payable]
+    pub fn distribute(&mut self, split_id: SplitId, vesting: Option<VestingSchedule>) -> Distribution {
+        self.assert_not_paused(PauseArea::Distributions);
+        let amount = env::attached_deposit().as_yoctonear();
+        assert!(amount > 0, "Attached deposit must be greater than 0");
+
+        self.internal_distribute(&split_id, NEAR_TOKEN, amount, &env::predecessor_account_id(), vesting)
+    }
+
+    /// NEP-141 receiver. `msg` names the split to distribute the received tokens to, e.g.
+    /// `{"distribute":{"split_id":"split-1-1"}}` or `{"distribute":{"repo_url":"github.com/owner/repo"}}`
+    /// (optionally with a `vesting` schedule), escrows them for a single contributor with
+    /// `{"escrow":{"github_username":"octocat"}}`, or tops up a split's treasury with
+    /// `{"fund_treasury":{"split_id":"split-1-1"}}`.
+    /// Only tokens added with `add_allowed_token` are accepted. The whole amount is always used, so
+    /// nothing is returned to the sender.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        self.assert_not_paused(PauseArea::Distributions);
+        let token_id = env::predecessor_account_id();
+        assert!(self.allowed_tokens.contains(&token_id), "Token {} is not accepted", token_id);
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+        let message: TokenReceiverMessage = near_sdk::serde_json::from_str(&msg).expect("Invalid transfer message");
+
+        match message {
+            TokenReceiverMessage::Distribute { split_id, repo_url, vesting } => {
+                let split_id = match (split_id, repo_url) {
+                    (Some(split_id), _) => split_id,
+                    (None, Some(repo_url)) => self.resolve_repo_split_id(&repo_url).cloned().expect("Split not found"),
+                    (None, None) => env::panic_str("Transfer message must name a split_id or repo_url"),
+                };
+                let distribution = self.internal_distribute(&split_id, token_id.as_str(), amount.0, &sender_id, vesting);
+                env::log_str(&format!(
+                    "Distributed {} of {} from {} as {}",
+                    distribution.amount, token_id, sender_id, distribution.id
+                ));
+                PromiseOrValue::Value(U128(0))
+            }
+            TokenReceiverMessage::Escrow { github_username } => {
+                let normalized_github = normalize_github_username(&github_username);
+                assert!(!normalized_github.is_empty(), "GitHub username cannot be empty");
+                // `sender_id` is vouched for by the token contract, which is on the allowlist checked above.
+                let initial_storage_usage = self.storage_checkpoint();
+                self.internal_store_pending(
+                    &normalized_github,
+                    amount.0,
+                    token_id.as_str(),
+                    &sender_id,
+                    PendingOrigin::default(),
+                );
+                self.process_pending_distributions(&normalized_github);
+                self.charge_storage(&sender_id, initial_storage_usage);
+                PromiseOrValue::Value(U128(0))
+            }
+            TokenReceiverMessage::FundTreasury { split_id } => {
+                self.internal_fund_treasury(&split_id, token_id.as_str(), amount.0, &sender_id);
+                PromiseOrValue::Value(U128(0))
+            }
+        }
+    }
+
+    /// Records a distribution paid outside the contract, e.g. by the caller's wallet or through chain
+    /// signatures, so it shows up in the split's payout history. Callable by an active worker or
+    /// the split owner; the record's storage is charged to the split owner. Transaction amounts must
+    /// add up to `amount`, and transactions start `Pending` or `Submitted`; their outcomes are
+    /// reported afterwards with `report_transaction_outcomes`.
+    pub fn record_distribution(
+        &mut self,
+        split_id: SplitId,
+        amount: U128,
+        token_id: Option<String>,
+        transactions: Vec<Transaction>,
+    ) -> DistributionId {
+        self.assert_not_paused(PauseArea::Distributions);
+        let split_owner: AccountId = {
+            let split = self.splits.get(&split_id).expect("Split not found");
+            split.owner.parse().expect("Invalid split owner")
+        };
+        let caller = env::predecessor_account_id();
+        if caller != split_owner {
+            self.assert_worker_caller();
+        }
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+        assert!(!transactions.is_empty(), "Transactions cannot be empty");
+        assert!(transactions.len() <= 200, "Too many transactions");
+        let mut total: u128 = 0;
+        for transaction in transactions.iter() {
+            let transaction_amount: u128 = transaction.amount.parse().expect("Invalid transaction amount");
+            total = total.checked_add(transaction_amount).expect("Transaction amounts overflow");
+            assert!(transaction.signature.is_none(), "Signatures are recorded by generate_chain_signature");
+            assert!(
+                matches!(transaction.status, TransactionStatus::Pending | TransactionStatus::Submitted),
+                "Transactions must be recorded as Pending or Submitted, not {:?}",
+                transaction.status
+            );
+        }
+        assert_eq!(total, amount.0, "Transaction amounts must add up to the distribution amount");
+
+        let initial_storage_usage = self.storage_checkpoint();
+        let split_version = self.split_versions.get(&split_id).map(|versions| versions.len() as u64).unwrap_or(0);
+        let distribution = Distribution {
+            id: self.next_distribution_id(),
+            split_id: split_id.clone(),
+            split_version,
+            amount: amount.0,
+            token_id: token_id.filter(|token| token != NEAR_TOKEN),
+            timestamp: env::block_timestamp(),
+            payer: caller.to_string(),
+            fan_out: DistributionNode {
+                split_id,
+                split_version,
+                amount: amount.0,
+                transactions: (0..transactions.len() as u32).collect(),
+                children: Vec::new(),
+            },
+            transactions,
+            vesting: None,
+            recorded: true,
+        };
+        self.internal_store_distribution(&distribution, &caller);
+        self.charge_storage(&split_owner, initial_storage_usage);
+        distribution.id
+    }
+
+    pub fn get_distribution(&self, distribution_id: DistributionId) -> Option<&Distribution> {
+        self.distributions.get(&distribution_id)
+    }
+
+    /// Lists a split's distributions, oldest first.
+    pub fn get_split_distributions(&self, split_id: SplitId, from_index: Option<u64>, limit: Option<u64>) -> Vec<&Distribution> {
+        let start = from_index.unwrap_or(0) as usize;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        self.split_distributions
+            .get(&split_id)
+            .map(|distribution_ids| {
+                distribution_ids
+                    .iter()
+                    .skip(start)
+                    .take(page_size)
+                    .filter_map(|distribution_id| self.distributions.get(distribution_id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_split_distribution_count(&self, split_id: SplitId) -> u64 {
+        self.split_distributions
+            .get(&split_id)
+            .map(|distribution_ids| distribution_ids.len() as u64)
+            .unwrap_or(0)
+    }
+
+    pub fn set_mpc_signer(&mut self, signer_id: AccountId) {
+        self.assert_role(Role::Treasurer);
+        self.mpc_signer = Some(signer_id.clone());
+        GitSplitsEvent::MpcSignerSet { signer_id }.emit();
+    }
+
+    pub fn get_mpc_signer(&self) -> Option<&AccountId> {
+        self.mpc_signer.as_ref()
+    }
+
+    /// Accepts transfers of a NEP-141 token through `ft_on_transfer`.
+    pub fn add_allowed_token(&mut self, token_id: AccountId) -> bool {
+        self.assert_role(Role::Treasurer);
+        let added = self.allowed_tokens.insert(token_id.clone());
+        if added {
+            GitSplitsEvent::TokenAdded { token_id }.emit();
+        }
+        added
+    }
+
+    /// Stops accepting a token. Balances and pending distributions already held in it stay claimable.
+    pub fn remove_allowed_token(&mut self, token_id: AccountId) -> bool {
+        self.assert_role(Role::Treasurer);
+        let removed = self.allowed_tokens.remove(&token_id);
+        if removed {
+            GitSplitsEvent::TokenRemoved { token_id }.emit();
+        }
+        removed
+    }
+
+    pub fn get_allowed_tokens(&self) -> Vec<AccountId> {
+        self.allowed_tokens.iter().cloned().collect()
+    }
+
+    /// Requests an MPC signature over `payload` (the 32-byte hash of the target-chain transaction) for
+    /// a transaction of a recorded distribution. The key is derived from `gitsplits/<split_id>/<chain_id>`
+    /// and the signature is persisted on the transaction by `on_chain_signature`. The attached deposit
+    /// is forwarded to the signer to cover its fee.
+    #[payable]
+    pub fn generate_chain_signature(
+        &mut self,
+        distribution_id: DistributionId,
+        transaction_index: u32,
+        payload: Vec<u8>,
+    ) -> Promise {
+        self.assert_not_paused(PauseArea::Distributions);
+        self.assert_worker_caller();
+        let signer_id = self.mpc_signer.clone().expect("MPC signer is not configured");
+        let payload: [u8; 32] = payload.try_into().expect("Payload must be 32 bytes");
+        let deposit = env::attached_deposit();
+        assert!(!deposit.is_zero(), "Attach a deposit to cover the signer fee");
+
+        let distribution = self.distributions.get(&distribution_id).expect("Distribution not found");
+        let transaction = distribution
+            .transactions
+            .get(transaction_index as usize)
+            .expect("Transaction not found");
+        let path = derivation_path(&distribution.split_id, &transaction.chain_id);
+
+        ext_signer::ext(signer_id)
+            .with_attached_deposit(deposit)
+            .sign(SignRequest {
+                payload,
+                path: path.clone(),
+                key_version: MPC_KEY_VERSION,
+            })
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_SIGNATURE_CALLBACK)
+                    .on_chain_signature(distribution_id, transaction_index, path),
+            )
+    }
+
+    #[private]
+    pub fn on_chain_signature(
+        &mut self,
+        distribution_id: DistributionId,
+        transaction_index: u32,
+        path: String,
+        #[callback_result] result: Result<SignatureResponse, PromiseError>,
+    ) -> ChainSignature {
+        let response = result.unwrap_or_else(|_| env::panic_str("MPC signer did not return a signature"));
+        let distribution = self.distributions.get_mut(&distribution_id).expect("Distribution not found");
+        let transaction = distribution
+            .transactions
+            .get_mut(transaction_index as usize)
+            .expect("Transaction not found");
+
+        let signature = ChainSignature {
+            big_r: response.big_r.affine_point,
+            s: response.s.scalar,
+            recovery_id: response.recovery_id,
+            derivation_path: path,
+            chain_id: transaction.chain_id.clone(),
+        };
+        transaction.signature = Some(signature.clone());
+        GitSplitsEvent::ChainSignatureRecorded {
+            distribution_id,
+            transaction_index,
+            chain_id: signature.chain_id.clone(),
+            derivation_path: signature.derivation_path.clone(),
+        }
+        .emit();
+        signature
+    }
+
+    /// Records the outcomes of payouts settled outside the contract (chain signatures, HOT Pay,
+    /// Ping Pay, ...). Transactions that reached a final status cannot be reported again, none can be
+    /// reported back to `Pending`, and `Completed` reports must carry the transaction hash.
+    pub fn report_transaction_outcomes(&mut self, distribution_id: DistributionId, reports: Vec<TransactionReport>) {
+        self.assert_not_paused(PauseArea::Distributions);
+        self.assert_worker_caller();
+        assert!(!reports.is_empty(), "Reports cannot be empty");
+        let distribution = self.distributions.get_mut(&distribution_id).expect("Distribution not found");
+        // Payouts made by the contract are settled by its own callbacks.
+        assert!(distribution.recorded, "Only recorded distributions can be reported");
+
+        for report in reports {
+            let transaction = distribution
+                .transactions
+                .get_mut(report.transaction_index as usize)
+                .expect("Transaction not found");
+            assert!(
+                !transaction.status.is_final(),
+                "Transaction {} is already {:?}",
+                report.transaction_index,
+                transaction.status
+            );
+            assert!(
+                report.status != TransactionStatus::Pending,
+                "Transaction {} cannot be reported as Pending",
+                report.transaction_index
+            );
+            assert!(
+                report.status != TransactionStatus::Failed || report.failure_reason.is_some(),
+                "Failed transactions must include a failure reason"
+            );
+            assert!(
+                report.status != TransactionStatus::Completed || report.tx_hash.is_some(),
+                "Completed transactions must include a transaction hash"
+            );
+
+            transaction.status = report.status;
+            if report.tx_hash.is_some() {
+                transaction.tx_hash = report.tx_hash;
+            }
+            transaction.failure_reason = report.failure_reason;
+            if report.target_block_height.is_some() {
+                transaction.target_block_height = report.target_block_height;
+            }
+            if report.target_timestamp.is_some() {
+                transaction.target_timestamp = report.target_timestamp;
+            }
+
+            GitSplitsEvent::TransactionStatusReported {
+                distribution_id: distribution_id.clone(),
+                transaction_index: report.transaction_index,
+                chain_id: transaction.chain_id.clone(),
+                status: transaction.status,
+                tx_hash: transaction.tx_hash.clone(),
+                failure_reason: transaction.failure_reason.clone(),
+            }
+            .emit();
+        }
+    }
+
+    pub fn store_verification(&mut self, github_username: String, x_username: String, wallet_address: AccountId) -> bool {
+        self.assert_not_paused(PauseArea::Verification);
+        self.assert_worker_caller();
+        let normalized_github = normalize_github_username(&github_username);
+        assert!(!normalized_github.is_empty(), "GitHub username cannot be empty");
+
+        if let Some(previous_wallet) = self.verified_wallets.get(&normalized_github).cloned() {
+            self.wallet_to_github.remove(&previous_wallet);
+        }
+        if let Some(existing_github_for_wallet) = self.wallet_to_github.get(&wallet_address).cloned() {
+            if existing_github_for_wallet != normalized_github {
+                self.verified_wallets.remove(&existing_github_for_wallet);
+                self.github_to_x_mappings.remove(&existing_github_for_wallet);
+            }
+        }
+
+        self.github_to_x_mappings.insert(normalized_github.clone(), x_username);
+        self.verified_wallets.insert(normalized_github.clone(), wallet_address.clone());
+        self.wallet_to_github.insert(wallet_address.clone(), normalized_github.clone());
+
+        let event = VerificationEntry {
+            github_username: normalized_github.clone(),
+            wallet_address: wallet_address.to_string(),
+            x_username: self.github_to_x_mappings.get(&normalized_github).cloned(),
+        };
+        GitSplitsEvent::VerificationStored(event).emit();
+        self.process_pending_distributions(&normalized_github);
         true
     }
 
     pub fn update_verification(&mut self, github_username: String, wallet_address: AccountId) -> bool {
+        self.assert_not_paused(PauseArea::Verification);
         self.assert_worker_caller();
         let normalized_github = normalize_github_username(&github_username);
         assert!(!normalized_github.is_empty(), "GitHub username cannot be empty");
@@ -418,12 +1788,13 @@ impl GitSplitsContract {
             wallet_address: wallet_address.to_string(),
             x_username: self.github_to_x_mappings.get(&normalized_github).cloned(),
         };
-        emit_verification_event("verification_updated", &event);
+        GitSplitsEvent::VerificationUpdated(event).emit();
         self.process_pending_distributions(&normalized_github);
         true
     }
 
     pub fn revoke_verification(&mut self, github_username: String) -> bool {
+        self.assert_not_paused(PauseArea::Verification);
         self.assert_worker_caller();
         let normalized_github = normalize_github_username(&github_username);
         if let Some(wallet_address) = self.verified_wallets.remove(&normalized_github) {
@@ -434,7 +1805,7 @@ impl GitSplitsContract {
                 wallet_address: wallet_address.to_string(),
                 x_username: removed_x,
             };
-            emit_verification_event("verification_revoked", &event);
+            GitSplitsEvent::VerificationRevoked(event).emit();
             true
         } else {
             false
@@ -461,49 +1832,30 @@ impl GitSplitsContract {
     }
 
     pub fn get_verification_count(&self) -> u64 {
-        self.verified_wallets.len() as u64
+        self.verified_wallets.len()
     }
 
     pub fn get_verified_wallets_page(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<VerificationEntry> {
-        let start = offset.unwrap_or(0) as usize;
         let page_size = limit.unwrap_or(50).min(200) as usize;
-        let mut all_entries: Vec<VerificationEntry> = self.verified_wallets.iter().map(|(github_username, account_id)| VerificationEntry {
-            github_username: github_username.clone(),
-            wallet_address: account_id.to_string(),
-            x_username: self.github_to_x_mappings.get(github_username).cloned(),
-        }).collect();
-        all_entries.sort_by(|a, b| a.github_username.cmp(&b.github_username));
-        all_entries
-            .into_iter()
-            .skip(start)
-            .take(page_size)
+        self.verified_wallets
+            .page(offset.unwrap_or(0), page_size)
+            .iter()
+            .map(|(github_username, account_id)| self.verification_entry(github_username, account_id))
             .collect()
     }
 
+    /// Lists verifications in username order, starting after `start_after`. `total` is the number
+    /// of verified wallets in the contract.
     pub fn get_verified_wallets_cursor(&self, start_after: Option<String>, limit: Option<u64>) -> VerificationPage {
         let page_size = limit.unwrap_or(50).min(200) as usize;
         let start_key = start_after.map(|s| normalize_github_username(&s));
 
-        let mut all_entries: Vec<VerificationEntry> = self.verified_wallets.iter().map(|(github_username, account_id)| VerificationEntry {
-            github_username: github_username.clone(),
-            wallet_address: account_id.to_string(),
-            x_username: self.github_to_x_mappings.get(github_username).cloned(),
-        }).collect();
-        all_entries.sort_by(|a, b| a.github_username.cmp(&b.github_username));
-
-        let filtered: Vec<VerificationEntry> = all_entries
-            .into_iter()
-            .filter(|entry| {
-                if let Some(ref key) = start_key {
-                    entry.github_username > *key
-                } else {
-                    true
-                }
-            })
+        let entries: Vec<VerificationEntry> = self
+            .verified_wallets
+            .page_after(start_key.as_deref(), page_size)
+            .iter()
+            .map(|(github_username, account_id)| self.verification_entry(github_username, account_id))
             .collect();
-
-        let total = filtered.len() as u64;
-        let entries: Vec<VerificationEntry> = filtered.into_iter().take(page_size).collect();
         let next_cursor = if entries.len() == page_size {
             entries.last().map(|entry| entry.github_username.clone())
         } else {
@@ -513,7 +1865,7 @@ impl GitSplitsContract {
         VerificationPage {
             entries,
             next_cursor,
-            total,
+            total: self.verified_wallets.len(),
         }
     }
 
@@ -530,40 +1882,56 @@ impl GitSplitsContract {
             .collect()
     }
 
+    /// Escrows the attached NEAR for a GitHub user until they verify a wallet. Token-denominated
+    /// escrow goes through `ft_transfer_call` with an `escrow` message instead.
+    #[payable]
     pub fn store_pending_distribution(&mut self, github_username: String, amount: u128, token: String) -> String {
-        self.assert_worker_caller();
+        self.assert_not_paused(PauseArea::Distributions);
         let normalized_github = normalize_github_username(&github_username);
-        let id = format!("pending-{}-{}", normalized_github, env::block_timestamp());
-        let pending_distribution = PendingDistribution {
-            id: id.clone(),
-            github_username: normalized_github.clone(),
+        assert!(!normalized_github.is_empty(), "GitHub username cannot be empty");
+        assert!(amount > 0, "Amount must be greater than 0");
+        assert_eq!(token, NEAR_TOKEN, "Token escrow must be funded with ft_transfer_call");
+        assert_eq!(
+            env::attached_deposit().as_yoctonear(),
             amount,
-            token,
-            timestamp: env::block_timestamp(),
-            claimed: false,
-        };
-        self.pending_distributions.insert(id.clone(), pending_distribution);
+            "Attached deposit must equal the escrowed amount"
+        );
+
+        let sponsor = env::predecessor_account_id();
+        let initial_storage_usage = self.storage_checkpoint();
+        let id = self.internal_store_pending(&normalized_github, amount, NEAR_TOKEN, &sponsor, PendingOrigin::default());
+        self.process_pending_distributions(&normalized_github);
+        self.charge_storage(&sponsor, initial_storage_usage);
         id
     }
 
+    /// Credits every funded, unclaimed pending distribution of a verified GitHub user to their
+    /// claimable balance. Returns the number of pending distributions credited.
+    pub fn claim_pending(&mut self, github_username: String) -> u64 {
+        self.assert_not_paused(PauseArea::Claims);
+        let normalized_github = normalize_github_username(&github_username);
+        assert!(
+            self.verified_wallets.contains_key(&normalized_github),
+            "GitHub username is not verified"
+        );
+        self.process_pending_distributions(&normalized_github)
+    }
+
     pub fn get_pending_distributions(&self, github_username: String) -> Vec<PendingDistribution> {
         let normalized_github = normalize_github_username(&github_username);
-        let mut result = Vec::new();
-        for distribution in self.pending_distributions.values() {
-            if normalize_github_username(&distribution.github_username) == normalized_github && !distribution.claimed {
-                result.push(distribution.clone());
-            }
-        }
-        result
+        self.pending_records(&normalized_github)
+            .filter(|distribution| !distribution.claimed && !distribution.expired)
+            .cloned()
+            .collect()
     }
 
     pub fn get_repo_verification_status(&self, repo_url: String) -> RepoVerificationStatus {
-        if let Some(split_id) = self.splits_by_repo.get(&repo_url) {
+        if let Some(split_id) = self.resolve_repo_split_id(&repo_url) {
             if let Some(split) = self.splits.get(split_id) {
                 let mut verified = Vec::new();
                 let mut unverified = Vec::new();
 
-                for contributor in split.contributors.iter() {
+                for contributor in split.contributors.iter().filter(|contributor| contributor.split_id.is_none()) {
                     let normalized_username = normalize_github_username(&contributor.github_username);
                     if let Some(wallet_address) = self.verified_wallets.get(&normalized_username) {
                         verified.push(VerificationEntry {
@@ -595,28 +1963,475 @@ impl GitSplitsContract {
         }
     }
 
-    fn process_pending_distributions(&mut self, github_username: &String) {
+    /// Splits `amount` of `token` between the contributors of a split, recursing into upstream
+    /// splits, and records the resulting distribution. Shares come from `allocation::allocate`, so
+    /// they add up to exactly `amount`.
+    fn internal_distribute(
+        &mut self,
+        split_id: &SplitId,
+        token: &str,
+        amount: u128,
+        payer: &AccountId,
+        vesting: Option<VestingSchedule>,
+    ) -> Distribution {
+        if let Some(schedule) = vesting.as_ref() {
+            schedule.assert_valid();
+        }
+        let initial_storage_usage = self.storage_checkpoint();
+        // Scheduled runs are paid by the contract out of the split's treasury.
+        let treasury_split_id = (*payer == env::current_account_id()).then(|| split_id.clone());
+        let distribution_id = self.next_distribution_id();
+        let mut run = DistributionRun {
+            distribution_id: distribution_id.clone(),
+            token: token.to_string(),
+            payer: payer.clone(),
+            treasury_split_id: treasury_split_id.clone(),
+            vesting: vesting.is_some(),
+            path: Vec::new(),
+            transactions: Vec::new(),
+            vesting_grants: Vec::new(),
+        };
+        let fan_out = self.distribute_to_split(split_id, amount, &mut run);
+        if vesting.is_some() {
+            self.internal_store_vesting_grants(&distribution_id, run.vesting_grants);
+        }
+
+        let distribution = Distribution {
+            id: distribution_id.clone(),
+            split_id: split_id.clone(),
+            split_version: fan_out.split_version,
+            amount,
+            token_id: if token == NEAR_TOKEN { None } else { Some(token.to_string()) },
+            timestamp: env::block_timestamp(),
+            payer: payer.to_string(),
+            transactions: run.transactions,
+            fan_out,
+            vesting,
+            recorded: false,
+        };
+        self.internal_store_distribution(&distribution, payer);
+
+        // The split owner covers the storage of scheduled runs.
+        let storage_payer = if treasury_split_id.is_some() {
+            let split = self.splits.get(split_id).expect("Split not found");
+            split.owner.parse().expect("Invalid split owner")
+        } else {
+            payer.clone()
+        };
+        self.charge_storage(&storage_payer, initial_storage_usage);
+        distribution
+    }
+
+    fn next_distribution_id(&mut self) -> DistributionId {
+        let distribution_id = format!("dist-{}-{}", env::block_height(), self.next_distribution_nonce);
+        self.next_distribution_nonce = self.next_distribution_nonce.saturating_add(1);
+        distribution_id
+    }
+
+    /// Persists a distribution, appends it to its split's history and emits `DistributionCreated`.
+    fn internal_store_distribution(&mut self, distribution: &Distribution, payer: &AccountId) {
+        self.distributions.insert(distribution.id.clone(), distribution.clone());
+        let split_distributions = self
+            .split_distributions
+            .entry(distribution.split_id.clone())
+            .or_insert_with(|| Vector::new(StorageKey::SplitDistributionsInner {
+                split_id: distribution.split_id.clone(),
+            }));
+        split_distributions.push(distribution.id.clone());
+        split_distributions.flush();
+        GitSplitsEvent::DistributionCreated {
+            distribution_id: distribution.id.clone(),
+            split_id: distribution.split_id.clone(),
+            amount: U128(distribution.amount),
+            token: distribution.token_id.clone().unwrap_or_else(|| NEAR_TOKEN.to_string()),
+            payer: payer.clone(),
+            recipients: distribution.transactions.len() as u64,
+        }
+        .emit();
+    }
+
+    /// Allocates `amount` to the contributors of `split_id`. GitHub contributors are credited to their
+    /// balance when verified and otherwise get a pending distribution, or get a vesting grant when the run
+    /// vests; upstream splits are expanded recursively. `run.path` holds the splits being expanded
+    /// and guards against cycles and nesting deeper than `MAX_SPLIT_DEPTH`.
+    fn distribute_to_split(&mut self, split_id: &SplitId, amount: u128, run: &mut DistributionRun) -> DistributionNode {
+        assert!(!run.path.contains(split_id), "Split {} is funded by itself", split_id);
+        assert!(run.path.len() as u32 <= MAX_SPLIT_DEPTH, "Splits are nested deeper than {} levels", MAX_SPLIT_DEPTH);
+        let split = self.splits.get(split_id).expect("Split not found").clone();
+        assert!(!split.contributors.is_empty(), "Split {} has no contributors", split_id);
+        run.path.push(split_id.clone());
+
+        let percentages: Vec<u128> = split.contributors.iter().map(|contributor| contributor.percentage).collect();
+        let shares = allocation::allocate(amount, &percentages);
+
+        let mut node = DistributionNode {
+            split_id: split_id.clone(),
+            split_version: self.split_versions.get(split_id).map(|versions| versions.len() as u64).unwrap_or(0),
+            amount,
+            transactions: Vec::new(),
+            children: Vec::new(),
+        };
+        for (contributor, share) in split.contributors.iter().zip(shares) {
+            if share == 0 {
+                continue;
+            }
+            if let Some(upstream_split_id) = &contributor.split_id {
+                let child = self.distribute_to_split(upstream_split_id, share, run);
+                node.children.push(child);
+                continue;
+            }
+
+            let transaction_index = run.transactions.len() as u32;
+            node.transactions.push(transaction_index);
+            let github_username = normalize_github_username(&contributor.github_username);
+            if run.vesting {
+                run.vesting_grants.push(VestingGrant {
+                    github_username: github_username.clone(),
+                    amount: share,
+                    withdrawn: 0,
+                });
+                run.transactions.push(Transaction {
+                    github_username: github_username.clone(),
+                    chain_id: "near".to_string(),
+                    recipient: github_username,
+                    amount: share.to_string(),
+                    tx_hash: None,
+                    signature: None,
+                    failure_reason: None,
+                    target_block_height: None,
+                    target_timestamp: None,
+                    status: TransactionStatus::Pending,
+                });
+            } else if self.verified_wallets.contains_key(&github_username) {
+                let token = run.token.clone();
+                self.internal_credit_balance(&github_username, &token, share);
+                GitSplitsEvent::BalanceCredited {
+                    github_username: github_username.clone(),
+                    token,
+                    amount: U128(share),
+                    distribution_id: run.distribution_id.clone(),
+                }
+                .emit();
+                run.transactions.push(Transaction {
+                    github_username: github_username.clone(),
+                    chain_id: "near".to_string(),
+                    recipient: github_username,
+                    amount: share.to_string(),
+                    tx_hash: None,
+                    signature: None,
+                    failure_reason: None,
+                    target_block_height: None,
+                    target_timestamp: None,
+                    status: TransactionStatus::Completed,
+                });
+            } else {
+                let (token, payer) = (run.token.clone(), run.payer.clone());
+                let origin = PendingOrigin {
+                    split_id: Some(split_id.clone()),
+                    treasury_split_id: run.treasury_split_id.clone(),
+                    transaction: Some(TransactionRef {
+                        distribution_id: run.distribution_id.clone(),
+                        transaction_index,
+                    }),
+                };
+                self.internal_store_pending(&github_username, share, &token, &payer, origin);
+                run.transactions.push(Transaction {
+                    github_username: github_username.clone(),
+                    chain_id: "near".to_string(),
+                    recipient: github_username,
+                    amount: share.to_string(),
+                    tx_hash: None,
+                    signature: None,
+                    failure_reason: None,
+                    target_block_height: None,
+                    target_timestamp: None,
+                    status: TransactionStatus::Pending,
+                });
+            }
+        }
+
+        run.path.pop();
+        node
+    }
+
+    /// Sends `amount` of `token` to `receiver_id`, either as a native NEAR transfer or as a
+    /// NEP-141 `ft_transfer` on the token contract.
+    fn internal_transfer(&self, token: &str, receiver_id: AccountId, amount: u128) -> Promise {
+        if token == NEAR_TOKEN {
+            Promise::new(receiver_id).transfer(NearToken::from_yoctonear(amount))
+        } else {
+            let token_id: AccountId = token.parse().expect("Invalid token account");
+            ext_ft::ext(token_id)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver_id, U128(amount), None)
+        }
+    }
+
+    /// Records funds held by the contract on behalf of a not-yet-verified GitHub user.
+    fn internal_store_pending(
+        &mut self,
+        github_username: &str,
+        amount: u128,
+        token: &str,
+        sponsor: &AccountId,
+        origin: PendingOrigin,
+    ) -> String {
+        let id = format!("pending-{}-{}", github_username, self.next_pending_nonce);
+        self.next_pending_nonce = self.next_pending_nonce.saturating_add(1);
+        self.pending_distributions.insert(id.clone(), PendingDistribution {
+            id: id.clone(),
+            github_username: github_username.to_string(),
+            amount,
+            token: token.to_string(),
+            timestamp: env::block_timestamp(),
+            claimed: false,
+            sponsor: Some(sponsor.to_string()),
+            split_id: origin.split_id.clone(),
+            treasury_split_id: origin.treasury_split_id,
+            funded: true,
+            expired: false,
+            transaction: origin.transaction,
+        });
+        let pending_ids = self
+            .pending_by_github
+            .entry(github_username.to_string())
+            .or_insert_with(|| Vector::new(StorageKey::PendingByGithubInner {
+                github_username: github_username.to_string(),
+            }));
+        pending_ids.push(id.clone());
+        pending_ids.flush();
+        GitSplitsEvent::PendingDistributionStored {
+            id: id.clone(),
+            github_username: github_username.to_string(),
+            amount: U128(amount),
+            token: token.to_string(),
+            sponsor: sponsor.clone(),
+            split_id: origin.split_id,
+        }
+        .emit();
+        id
+    }
+
+    /// Credits the escrowed funds of a verified GitHub user to their claimable balance, which their
+    /// wallet pulls with `withdraw`, and completes the distribution transactions the funds belong to.
+    fn process_pending_distributions(&mut self, github_username: &str) -> u64 {
+        if self.pause_state.claims {
+            return 0;
+        }
         let normalized_github = normalize_github_username(github_username);
-        let pending_ids: Vec<String> = self.pending_distributions.values()
-            .filter(|d| normalize_github_username(&d.github_username) == normalized_github && !d.claimed)
-            .map(|d| d.id.clone())
-            .collect();
+        let wallet_address = match self.verified_wallets.get(&normalized_github) {
+            Some(wallet_address) => wallet_address.clone(),
+            None => return 0,
+        };
 
-        for id in pending_ids {
-            if let Some(dist) = self.pending_distributions.get_mut(&id) {
-                dist.claimed = true;
+        let mut claims_by_token: BTreeMap<String, (u128, Vec<String>)> = BTreeMap::new();
+        for d in self.pending_records(&normalized_github) {
+            if d.funded && !d.claimed && !d.expired {
+                let entry = claims_by_token.entry(d.token.clone()).or_default();
+                entry.0 += d.amount;
+                entry.1.push(d.id.clone());
             }
         }
+
+        let mut claimed_count: u64 = 0;
+        for (token, (amount, pending_ids)) in claims_by_token {
+            for id in pending_ids.iter() {
+                if let Some(pending) = self.pending_distributions.get_mut(id) {
+                    pending.claimed = true;
+                }
+            }
+            claimed_count += pending_ids.len() as u64;
+            self.set_linked_transactions_status(&pending_ids, TransactionStatus::Completed, None);
+            self.internal_credit_balance(&normalized_github, &token, amount);
+            GitSplitsEvent::PendingClaimed {
+                github_username: normalized_github.clone(),
+                wallet_address: wallet_address.clone(),
+                amount: U128(amount),
+                token,
+                pending_ids,
+            }
+            .emit();
+        }
+        claimed_count
+    }
+
+    /// Moves the distribution transactions that pending distributions were created for to `status`.
+    pub(crate) fn set_linked_transactions_status(
+        &mut self,
+        pending_ids: &[String],
+        status: TransactionStatus,
+        failure_reason: Option<String>,
+    ) {
+        for id in pending_ids.iter() {
+            let Some(linked) = self.pending_distributions.get(id).and_then(|pending| pending.transaction.clone()) else {
+                continue;
+            };
+            if let Some(transaction) = self
+                .distributions
+                .get_mut(&linked.distribution_id)
+                .and_then(|distribution| distribution.transactions.get_mut(linked.transaction_index as usize))
+            {
+                transaction.status = status;
+                transaction.failure_reason = failure_reason.clone();
+            }
+        }
+    }
+
+    fn verification_entry(&self, github_username: &String, account_id: &AccountId) -> VerificationEntry {
+        VerificationEntry {
+            github_username: github_username.clone(),
+            wallet_address: account_id.to_string(),
+            x_username: self.github_to_x_mappings.get(github_username).cloned(),
+        }
+    }
+
+    /// Pending distributions recorded for a normalized GitHub username, oldest first.
+    fn pending_records<'a>(&'a self, github_username: &str) -> impl Iterator<Item = &'a PendingDistribution> + 'a {
+        self.pending_by_github
+            .get(github_username)
+            .into_iter()
+            .flat_map(|pending_ids| pending_ids.iter())
+            .filter_map(|id| self.pending_distributions.get(id))
     }
 
-    fn assert_worker_caller(&self) {
+    fn is_code_hash_allowed(&self, code_hash: &str) -> bool {
+        self.allowed_code_hashes
+            .iter()
+            .any(|existing_hash| attestation::normalize_code_hash(existing_hash) == code_hash)
+    }
+
+    /// Whether the worker's code hash is allowed and was not revoked after the worker registered.
+    fn is_worker_code_hash_valid(&self, worker: &WorkerInfo) -> bool {
+        let code_hash = attestation::normalize_code_hash(&worker.code_hash);
+        self.is_code_hash_allowed(&code_hash)
+            && self
+                .revoked_code_hashes
+                .get(&code_hash)
+                .is_none_or(|revoked_at| worker.registered_at > *revoked_at)
+    }
+
+    fn is_worker_active(&self, worker: &WorkerInfo) -> bool {
+        worker.registered_at.saturating_add(self.worker_registration_ttl) > env::block_timestamp()
+            && self.is_worker_code_hash_valid(worker)
+    }
+
+    fn worker_status(&self, account_id: &AccountId, worker: &WorkerInfo) -> WorkerStatus {
+        WorkerStatus {
+            account_id: account_id.to_string(),
+            code_hash: worker.code_hash.clone(),
+            registered_at: worker.registered_at,
+            last_active_at: worker.last_active_at,
+            expires_at: worker.registered_at.saturating_add(self.worker_registration_ttl),
+            active: self.is_worker_active(worker),
+        }
+    }
+
+    /// Asserts that the caller is an active worker and records the call as worker activity.
+    fn assert_worker_caller(&mut self) {
+        let caller = env::predecessor_account_id();
+        let worker = self
+            .registered_workers
+            .get(&caller)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Only registered workers can call this method"));
         assert!(
-            self.registered_workers.contains_key(&env::predecessor_account_id()),
-            "Only registered workers can call this method"
+            worker.registered_at.saturating_add(self.worker_registration_ttl) > env::block_timestamp(),
+            "Worker registration has expired; re-attest with register_worker"
+        );
+        assert!(self.is_worker_code_hash_valid(&worker), "Worker code hash has been revoked");
+
+        self.worker_accounts.insert(caller.clone());
+        self.registered_workers.insert(caller, WorkerInfo {
+            last_active_at: env::block_timestamp(),
+            ..worker
+        });
+    }
+
+    fn assert_not_paused(&self, area: PauseArea) {
+        assert!(!self.pause_state.is_paused(area), "{:?} is paused", area);
+    }
+
+    /// Looks a repository up by its canonical key, falling back to the raw string for splits
+    /// that `migrate_repo_keys` has not re-indexed yet.
+    fn resolve_repo_split_id(&self, repo_url: &str) -> Option<&SplitId> {
+        repo::canonical_repo_key(repo_url)
+            .ok()
+            .and_then(|repo_key| self.splits_by_repo.get(&repo_key))
+            .or_else(|| self.splits_by_repo.get(repo_url))
+    }
+
+    /// Panics if an upstream split named in `contributors` does not exist, or if funding it from
+    /// `split_id` would route funds back to `split_id` or nest splits deeper than `MAX_SPLIT_DEPTH`.
+    /// Depth counts from the furthest split that funds `split_id`, so an edit cannot push the splits
+    /// funding it past the limit either.
+    fn assert_valid_upstream_splits(&self, split_id: &SplitId, contributors: &[Contributor]) {
+        let funding_depth = self.funding_depth(split_id);
+        for upstream_split_id in contributors.iter().filter_map(|contributor| contributor.split_id.as_ref()) {
+            self.assert_acyclic_upstream(upstream_split_id, split_id, funding_depth + 1);
+        }
+    }
+
+    /// Number of split levels that route funds into `split_id`.
+    fn funding_depth(&self, split_id: &SplitId) -> u32 {
+        self.funding_splits
+            .get(split_id)
+            .map(|funding_split_ids| {
+                funding_split_ids
+                    .iter()
+                    .map(|funding_split_id| self.funding_depth(funding_split_id) + 1)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0)
+    }
+
+    /// Moves `split_id` between the `funding_splits` entries of the upstream splits it stopped and
+    /// started funding.
+    fn update_funding_splits(&mut self, split_id: &SplitId, previous: &[Contributor], current: &[Contributor]) {
+        let upstream_ids = |contributors: &[Contributor]| -> BTreeSet<SplitId> {
+            contributors.iter().filter_map(|contributor| contributor.split_id.clone()).collect()
+        };
+        let (previous, current) = (upstream_ids(previous), upstream_ids(current));
+        for upstream_split_id in previous.difference(&current) {
+            if let Some(funding_split_ids) = self.funding_splits.get_mut(upstream_split_id) {
+                funding_split_ids.retain(|funding_split_id| funding_split_id != split_id);
+                if funding_split_ids.is_empty() {
+                    self.funding_splits.remove(upstream_split_id);
+                }
+            }
+        }
+        for upstream_split_id in current.difference(&previous) {
+            self.funding_splits
+                .entry(upstream_split_id.clone())
+                .or_default()
+                .push(split_id.clone());
+        }
+    }
+
+    fn assert_acyclic_upstream(&self, split_id: &SplitId, funded_split_id: &SplitId, depth: u32) {
+        assert!(split_id != funded_split_id, "Split {} would fund itself", funded_split_id);
+        assert!(depth <= MAX_SPLIT_DEPTH, "Splits cannot be nested deeper than {} levels", MAX_SPLIT_DEPTH);
+        let split = self
+            .splits
+            .get(split_id)
+            .unwrap_or_else(|| env::panic_str(&format!("Upstream split {} not found", split_id)));
+        // Splits cannot be emptied once allocated, so an upstream split keeps its contributors.
+        assert!(!split.contributors.is_empty(), "Upstream split {} has no contributors", split_id);
+        for upstream_split_id in split.contributors.iter().filter_map(|contributor| contributor.split_id.as_ref()) {
+            self.assert_acyclic_upstream(upstream_split_id, funded_split_id, depth + 1);
+        }
+    }
+
+    fn assert_split_owner(&self, split_id: &SplitId) {
+        let split = self.splits.get(split_id).expect("Split not found");
+        assert_eq!(
+            env::predecessor_account_id().as_str(),
+            split.owner,
+            "Only the split owner can call this method"
         );
     }
 
-    #[allow(dead_code)]
     fn assert_owner(&self) {
         assert_eq!(
             env::predecessor_account_id(),
@@ -624,20 +2439,236 @@ impl GitSplitsContract {
             "Only the owner can call this method"
         );
     }
+
+    fn assert_role(&self, role: Role) {
+        assert!(
+            self.has_role(role, env::predecessor_account_id()),
+            "Caller is missing the {:?} role",
+            role
+        );
+    }
 }
 
 fn normalize_github_username(input: &str) -> String {
     input.trim().trim_start_matches('@').to_ascii_lowercase()
 }
 
-fn emit_verification_event(event_name: &str, entry: &VerificationEntry) {
-    env::log_str(
-        &json!({
-            "standard": "gitsplits",
-            "version": "1.0.0",
-            "event": event_name,
-            "data": entry,
-        })
-        .to_string(),
-    );
+/// Validates a contributor list and normalizes usernames. Panics on empty, duplicate or
+/// zero-percentage entries, on entries naming both or neither of a username and an upstream
+/// split, and when percentages do not add up to `TOTAL_PERCENTAGE`.
+fn normalize_contributors(contributors: &[Contributor]) -> Vec<Contributor> {
+    assert!(!contributors.is_empty(), "Contributors cannot be empty");
+    assert!(contributors.len() <= 200, "Too many contributors");
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut seen_splits: HashSet<SplitId> = HashSet::new();
+    let mut normalized_contributors: Vec<Contributor> = Vec::with_capacity(contributors.len());
+    for contributor in contributors.iter() {
+        assert!(contributor.percentage > 0, "Contributor percentage must be > 0");
+        let normalized_username = normalize_github_username(&contributor.github_username);
+
+        if let Some(upstream_split_id) = &contributor.split_id {
+            assert!(
+                normalized_username.is_empty(),
+                "Contributor cannot name both a GitHub username and a split"
+            );
+            assert!(seen_splits.insert(upstream_split_id.clone()), "Duplicate contributor split");
+            normalized_contributors.push(Contributor {
+                github_username: String::new(),
+                account_id: None,
+                percentage: contributor.percentage,
+                split_id: Some(upstream_split_id.clone()),
+            });
+            continue;
+        }
+
+        assert!(!normalized_username.is_empty(), "Contributor username cannot be empty");
+        assert!(
+            seen.insert(normalized_username.clone()),
+            "Duplicate contributor username"
+        );
+        normalized_contributors.push(Contributor {
+            github_username: normalized_username,
+            account_id: contributor.account_id.clone(),
+            percentage: contributor.percentage,
+            split_id: None,
+        });
+    }
+
+    let total_percentage: u128 = normalized_contributors.iter()
+        .map(|c| c.percentage)
+        .sum();
+
+    assert_eq!(total_percentage, TOTAL_PERCENTAGE, "Percentages must add up to 100%");
+    normalized_contributors
+}
+
+/// Hex-encoded sha256 of the Borsh serialization of a normalized contributor list.
+fn contributors_hash(contributors: &[Contributor]) -> String {
+    let bytes = near_sdk::borsh::to_vec(contributors).expect("Failed to serialize contributors");
+    env::sha256(&bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Derivation path of the MPC key that signs a split's payouts on a given chain.
+fn derivation_path(split_id: &str, chain_id: &str) -> String {
+    format!("gitsplits/{}/{}", split_id, chain_id.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    const WORKER: &str = "worker.near";
+    const OWNER: &str = "owner.near";
+    const CODE_HASH: &str = "c0de";
+
+    fn set_caller(account_id: &str, deposit: u128) {
+        set_caller_at(account_id, deposit, 1);
+    }
+
+    fn set_caller_at(account_id: &str, deposit: u128, block_timestamp: u64) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.parse().unwrap())
+            .attached_deposit(NearToken::from_yoctonear(deposit))
+            .block_timestamp(block_timestamp)
+            .build());
+    }
+
+    /// A contract with a registered worker and storage balances for the worker and `OWNER`.
+    fn setup() -> GitSplitsContract {
+        set_caller(OWNER, 0);
+        let mut contract = GitSplitsContract::new();
+        contract.allowed_code_hashes.push(CODE_HASH.to_string());
+        contract.registered_workers.insert(WORKER.parse().unwrap(), WorkerInfo {
+            code_hash: CODE_HASH.to_string(),
+            registered_at: 1,
+            last_active_at: 1,
+        });
+        for account_id in [WORKER, OWNER] {
+            set_caller(account_id, NearToken::from_near(1).as_yoctonear());
+            contract.storage_deposit(None, None);
+        }
+        contract
+    }
+
+    fn create_split(contract: &mut GitSplitsContract, repo_url: &str) -> SplitId {
+        set_caller(WORKER, 0);
+        contract.create_split(repo_url.to_string(), OWNER.parse().unwrap())
+    }
+
+    fn user(github_username: &str, percentage: u128) -> Contributor {
+        Contributor {
+            github_username: github_username.to_string(),
+            account_id: None,
+            percentage,
+            split_id: None,
+        }
+    }
+
+    fn upstream(split_id: &SplitId, percentage: u128) -> Contributor {
+        Contributor {
+            github_username: String::new(),
+            account_id: None,
+            percentage,
+            split_id: Some(split_id.clone()),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "has no contributors")]
+    fn upstream_split_without_contributors_is_rejected() {
+        let mut contract = setup();
+        let funded = create_split(&mut contract, "github.com/owner/a");
+        let empty = create_split(&mut contract, "github.com/owner/b");
+        set_caller(OWNER, 0);
+        contract.update_split(
+            funded,
+            vec![user("alice", TOTAL_PERCENTAGE / 2), upstream(&empty, TOTAL_PERCENTAGE / 2)],
+            None,
+        );
+    }
+
+    #[test]
+    fn upstream_split_with_contributors_receives_its_share() {
+        let mut contract = setup();
+        let funded = create_split(&mut contract, "github.com/owner/a");
+        let upstream_id = create_split(&mut contract, "github.com/owner/b");
+        set_caller(OWNER, 0);
+        contract.update_split(upstream_id.clone(), vec![user("bob", TOTAL_PERCENTAGE)], None);
+        contract.update_split(
+            funded.clone(),
+            vec![user("alice", TOTAL_PERCENTAGE / 2), upstream(&upstream_id, TOTAL_PERCENTAGE / 2)],
+            None,
+        );
+
+        set_caller(OWNER, 1_000);
+        let distribution = contract.distribute(funded, None);
+        assert_eq!(distribution.transactions.len(), 2);
+    }
+
+    #[test]
+    fn failed_vested_payout_is_retried() {
+        let mut contract = setup();
+        let split_id = create_split(&mut contract, "github.com/owner/a");
+        set_caller(OWNER, 0);
+        contract.update_split(split_id.clone(), vec![user("alice", TOTAL_PERCENTAGE)], None);
+        contract.verified_wallets.insert("alice".to_string(), "alice.near".parse().unwrap());
+        contract.wallet_to_github.insert("alice.near".parse().unwrap(), "alice".to_string());
+
+        set_caller(OWNER, 1_000);
+        let schedule = VestingSchedule { start: 0, cliff: 0, duration: 1 };
+        let distribution_id = contract.distribute(split_id, Some(schedule)).id;
+        set_caller("alice.near", 0);
+        assert_eq!(contract.withdraw_vested(distribution_id.clone()).0, 1_000);
+
+        let status = |contract: &GitSplitsContract| contract.distributions.get(&distribution_id).unwrap().transactions[0].status;
+        assert_eq!(status(&contract), TransactionStatus::Submitted);
+        contract.on_vested_withdrawn(
+            distribution_id.clone(),
+            "alice".to_string(),
+            "alice.near".parse().unwrap(),
+            NEAR_TOKEN.to_string(),
+            U128(1_000),
+            Err(PromiseError::Failed),
+        );
+        assert_eq!(status(&contract), TransactionStatus::Failed);
+        assert_eq!(contract.get_failed_distributions(None, None), vec![distribution_id.clone()]);
+
+        set_caller("anyone.near", 0);
+        assert_eq!(contract.retry_failed(distribution_id.clone()), 1);
+        assert_eq!(status(&contract), TransactionStatus::Submitted);
+        assert!(contract.get_failed_distributions(None, None).is_empty());
+        contract.on_vested_withdrawn(
+            distribution_id.clone(),
+            "alice".to_string(),
+            "alice.near".parse().unwrap(),
+            NEAR_TOKEN.to_string(),
+            U128(1_000),
+            Ok(()),
+        );
+        assert_eq!(status(&contract), TransactionStatus::Completed);
+    }
+
+    #[test]
+    fn expired_pending_refund_settles_its_transaction() {
+        let mut contract = setup();
+        let split_id = create_split(&mut contract, "github.com/owner/a");
+        set_caller(OWNER, 0);
+        contract.update_split(split_id.clone(), vec![user("alice", TOTAL_PERCENTAGE)], None);
+        contract.set_default_expiry_policy(Some(ExpiryPolicy {
+            expires_after: expiry::MIN_PENDING_EXPIRY_NS,
+            action: expiry::ExpiryAction::RefundSponsor,
+        }));
+
+        set_caller(OWNER, 1_000);
+        let distribution_id = contract.distribute(split_id, None).id;
+        let status = |contract: &GitSplitsContract| contract.distributions.get(&distribution_id).unwrap().transactions[0].status;
+        assert_eq!(status(&contract), TransactionStatus::Pending);
+
+        set_caller_at("anyone.near", 0, 1 + expiry::MIN_PENDING_EXPIRY_NS);
+        assert_eq!(contract.sweep_expired_pending(None, None), 1);
+        assert_eq!(status(&contract), TransactionStatus::Refunded);
+    }
 }
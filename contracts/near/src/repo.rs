@@ -0,0 +1,124 @@
+//! Canonical repository identifiers.
+//!
+//! Splits are indexed by a canonical `host/owner/name` key so that the many ways of writing the
+//! same repository (`https://github.com/Owner/Repo`, `github.com/owner/repo.git`, `owner/repo`,
+//! `git@github.com:owner/repo.git`, ...) resolve to one split.
+
+const DEFAULT_HOST: &str = "github.com";
+
+/// A parsed, lowercased repository identifier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepoId {
+    pub host: String,
+    pub owner: String,
+    pub name: String,
+}
+
+impl RepoId {
+    /// Parses a repository URL or `owner/name` shorthand. Paths below the repository
+    /// (e.g. `/tree/main`) are ignored.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut rest = input.trim().to_ascii_lowercase();
+        for scheme in ["https://", "http://", "ssh://", "git://"] {
+            if let Some(stripped) = rest.strip_prefix(scheme) {
+                rest = stripped.to_string();
+                break;
+            }
+        }
+        if let Some(stripped) = rest.strip_prefix("git@") {
+            // scp-like syntax: git@github.com:owner/repo.git
+            rest = stripped.replacen(':', "/", 1);
+        }
+        if let Some(stripped) = rest.strip_prefix("www.") {
+            rest = stripped.to_string();
+        }
+
+        let segments: Vec<&str> = rest
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        let (host, path) = match segments.first() {
+            Some(first) if first.contains('.') => (first.to_string(), &segments[1..]),
+            _ => (DEFAULT_HOST.to_string(), &segments[..]),
+        };
+        if path.len() < 2 {
+            return Err(format!("Repository must be in owner/name form: {}", input));
+        }
+
+        let owner = path[0].to_string();
+        let name = path[1].trim_end_matches(".git").to_string();
+        if !is_valid_segment(&owner) || !is_valid_segment(&name) {
+            return Err(format!("Invalid repository identifier: {}", input));
+        }
+
+        Ok(Self { host, owner, name })
+    }
+
+    /// Key used to index splits, e.g. `github.com/owner/repo`.
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.host, self.owner, self.name)
+    }
+}
+
+/// Canonical key for `input`, if it parses as a repository identifier.
+pub fn canonical_repo_key(input: &str) -> Result<String, String> {
+    RepoId::parse(input).map(|repo| repo.key())
+}
+
+fn is_valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equivalent_forms_share_a_key() {
+        let cases = [
+            ("https://github.com/Owner/Repo", "github.com/owner/repo"),
+            ("github.com/owner/repo.git", "github.com/owner/repo"),
+            ("owner/repo", "github.com/owner/repo"),
+            ("  Owner/Repo ", "github.com/owner/repo"),
+            ("git@github.com:owner/repo.git", "github.com/owner/repo"),
+            ("ssh://git@github.com/owner/repo.git", "github.com/owner/repo"),
+            ("http://www.github.com/owner/repo", "github.com/owner/repo"),
+            ("https://github.com/owner/repo/", "github.com/owner/repo"),
+            ("https://github.com/owner/repo/tree/main", "github.com/owner/repo"),
+            ("https://github.com/owner/repo/blob/main/README.md", "github.com/owner/repo"),
+            ("https://github.com/owner/repo?tab=readme#top", "github.com/owner/repo"),
+            ("https://gitlab.com/Group/Project.git", "gitlab.com/group/project"),
+            ("owner/my.repo_name-2", "github.com/owner/my.repo_name-2"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(canonical_repo_key(input).as_deref(), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        for input in [
+            "",
+            "   ",
+            "repo",
+            "https://github.com/owner",
+            "github.com/",
+            "owner/..",
+            "owner/re po",
+            "owner/repo!",
+            "@owner/repo",
+            "https://github.com/owner/.git",
+        ] {
+            assert!(canonical_repo_key(input).is_err(), "{}", input);
+        }
+    }
+}
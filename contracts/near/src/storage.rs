@@ -0,0 +1,202 @@
+//! NEP-145 storage management.
+//!
+//! Splits, split versions and pending distributions are paid for by the account responsible for
+//! them: the split owner for split records and the sponsor for pending distributions. Those
+//! accounts deposit NEAR with `storage_deposit`; each operation measures the contract's storage
+//! growth and charges it against the responsible account's balance, refusing the operation when
+//! the balance does not cover it.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, NearToken, Promise};
+
+use crate::events::GitSplitsEvent;
+use crate::{GitSplitsContract, GitSplitsContractExt};
+
+/// Minimum deposit to register an account, covering its own balance record
+pub const STORAGE_BALANCE_MIN: u128 = 1_250_000_000_000_000_000_000;
+
+/// Storage balance held for an account, in yoctoNEAR
+#[derive(near_sdk::borsh::BorshDeserialize, near_sdk::borsh::BorshSerialize, Clone, Debug, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct StorageBalanceRecord {
+    pub total: u128,
+    pub used: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+impl From<&StorageBalanceRecord> for StorageBalance {
+    fn from(record: &StorageBalanceRecord) -> Self {
+        Self {
+            total: U128(record.total),
+            available: U128(record.total - record.used),
+        }
+    }
+}
+
+#[near_bindgen]
+impl GitSplitsContract {
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let deposit = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registered = self.storage_balances.contains_key(&account_id);
+
+        let credited = if registration_only.unwrap_or(false) {
+            let required = if registered { 0 } else { STORAGE_BALANCE_MIN };
+            assert!(deposit >= required, "Deposit is less than the minimum storage balance");
+            let refund = deposit - required;
+            if refund > 0 {
+                let _ = Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
+            }
+            required
+        } else {
+            if !registered {
+                assert!(deposit >= STORAGE_BALANCE_MIN, "Deposit is less than the minimum storage balance");
+            }
+            deposit
+        };
+
+        let record = self.storage_balances.entry(account_id.clone()).or_default();
+        record.total += credited;
+        let balance = StorageBalance::from(&*record);
+        if credited > 0 {
+            GitSplitsEvent::StorageDeposited {
+                account_id,
+                amount: U128(credited),
+            }
+            .emit();
+        }
+        balance
+    }
+
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let record = self
+            .storage_balances
+            .get_mut(&account_id)
+            .unwrap_or_else(|| env::panic_str("Account is not registered for storage"));
+        let available = record.total - record.used;
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        assert!(amount <= available, "Amount exceeds the available storage balance");
+
+        record.total -= amount;
+        let balance = StorageBalance::from(&*record);
+        if amount > 0 {
+            let _ = Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(amount));
+            GitSplitsEvent::StorageWithdrawn {
+                account_id,
+                amount: U128(amount),
+            }
+            .emit();
+        }
+        balance
+    }
+
+    /// Closes an account's storage balance and refunds it. Only possible once nothing is charged
+    /// against it; `force` is not supported because the records it pays for cannot be dropped.
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        assert!(!force.unwrap_or(false), "Forced unregistration is not supported");
+        let account_id = env::predecessor_account_id();
+        match self.storage_balances.get(&account_id) {
+            Some(record) => {
+                assert_eq!(record.used, 0, "Storage balance is still in use");
+                let refund = record.total;
+                self.storage_balances.remove(&account_id);
+                if refund > 0 {
+                    let _ = Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(refund));
+                    GitSplitsEvent::StorageWithdrawn {
+                        account_id,
+                        amount: U128(refund),
+                    }
+                    .emit();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_balances.get(&account_id).map(StorageBalance::from)
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(STORAGE_BALANCE_MIN),
+            max: None,
+        }
+    }
+}
+
+impl GitSplitsContract {
+    /// Flushes the collections whose growth is charged to accounts and returns the storage usage
+    /// to measure an operation against.
+    pub(crate) fn storage_checkpoint(&mut self) -> u64 {
+        self.flush_charged_collections();
+        env::storage_usage()
+    }
+
+    /// Charges the storage growth since `initial_storage_usage` (from `storage_checkpoint`) to
+    /// `account_id`, or releases the cost of freed storage back to it.
+    pub(crate) fn charge_storage(&mut self, account_id: &AccountId, initial_storage_usage: u64) {
+        self.flush_charged_collections();
+        let current_storage_usage = env::storage_usage();
+        let byte_cost = env::storage_byte_cost().as_yoctonear();
+        let record = self
+            .storage_balances
+            .get_mut(account_id)
+            .unwrap_or_else(|| env::panic_str(&format!("{} is not registered for storage", account_id)));
+
+        if current_storage_usage >= initial_storage_usage {
+            let cost = u128::from(current_storage_usage - initial_storage_usage) * byte_cost;
+            assert!(
+                record.used + cost <= record.total,
+                "Insufficient storage balance for {}: {} yoctoNEAR required",
+                account_id,
+                cost
+            );
+            record.used += cost;
+        } else {
+            let refund = u128::from(initial_storage_usage - current_storage_usage) * byte_cost;
+            record.used = record.used.saturating_sub(refund);
+        }
+    }
+
+    /// `store` collections buffer writes until flushed, so they must be written out before
+    /// `env::storage_usage` reflects them.
+    fn flush_charged_collections(&mut self) {
+        self.splits.flush();
+        self.splits_by_repo.flush();
+        self.distributions.flush();
+        self.split_distributions.flush();
+        self.split_versions.flush();
+        self.split_update_approvals.flush();
+        self.distribution_schedules.flush();
+        self.split_expiry_policies.flush();
+        self.pending_distributions.flush();
+        self.pending_by_github.flush();
+        self.claimable_balances.flush();
+        self.vesting_grants.flush();
+        self.github_vesting.flush();
+        self.split_treasuries.flush();
+        self.funding_splits.flush();
+    }
+}
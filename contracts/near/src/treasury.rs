@@ -0,0 +1,215 @@
+//! Split treasuries and recurring distributions.
+//!
+//! Anyone can top up a split's treasury, in NEAR with `fund_split_treasury` or in a NEP-141 token
+//! with an `ft_transfer_call` whose `msg` is `{"fund_treasury":{"split_id":"..."}}`. The split owner
+//! sets schedules that pay a fixed amount of a token every `interval`. Once a schedule is due any
+//! account can call `execute_due_distribution`, which distributes one run of every due schedule
+//! the treasury can cover. Each call pays at most one run per schedule and advances it by a single
+//! interval, so runs missed while the treasury was empty are caught up by calling again.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::events::GitSplitsEvent;
+use crate::{Distribution, GitSplitsContract, GitSplitsContractExt, PauseArea, SplitId, NEAR_TOKEN};
+
+/// Maximum number of schedules a split may have, one per token
+pub const MAX_SCHEDULES_PER_SPLIT: usize = 10;
+
+/// Recurring payout of `amount` of `token` from a split's treasury
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionSchedule {
+    pub token: String,
+    pub amount: U128,
+    /// Time between runs, in nanoseconds
+    pub interval: u64,
+    /// Earliest time the next run may execute
+    pub next_run_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryBalance {
+    pub token: String,
+    pub amount: U128,
+}
+
+#[near_bindgen]
+impl GitSplitsContract {
+    /// Adds the attached NEAR to a split's treasury.
+    #[payable]
+    pub fn fund_split_treasury(&mut self, split_id: SplitId) -> U128 {
+        self.assert_not_paused(PauseArea::Distributions);
+        let amount = env::attached_deposit().as_yoctonear();
+        assert!(amount > 0, "Attached deposit must be greater than 0");
+        self.internal_fund_treasury(&split_id, NEAR_TOKEN, amount, &env::predecessor_account_id())
+    }
+
+    /// Creates or replaces the split's schedule for `token`. The first run is due at `start_at`,
+    /// or immediately when omitted. Only the split owner can call this.
+    pub fn set_distribution_schedule(
+        &mut self,
+        split_id: SplitId,
+        token: Option<AccountId>,
+        amount: U128,
+        interval: u64,
+        start_at: Option<u64>,
+    ) {
+        self.assert_not_paused(PauseArea::SplitEdits);
+        self.assert_split_owner(&split_id);
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+        assert!(interval > 0, "Interval must be greater than 0");
+        let token = token.map(|token| token.to_string()).unwrap_or_else(|| NEAR_TOKEN.to_string());
+        let schedule = DistributionSchedule {
+            token: token.clone(),
+            amount,
+            interval,
+            next_run_at: start_at.unwrap_or_else(env::block_timestamp),
+        };
+
+        let initial_storage_usage = self.storage_checkpoint();
+        let schedules = self.distribution_schedules.entry(split_id.clone()).or_default();
+        match schedules.iter_mut().find(|existing| existing.token == token) {
+            Some(existing) => *existing = schedule.clone(),
+            None => {
+                assert!(schedules.len() < MAX_SCHEDULES_PER_SPLIT, "Too many schedules for this split");
+                schedules.push(schedule.clone());
+            }
+        }
+        let split_owner = env::predecessor_account_id();
+        self.charge_storage(&split_owner, initial_storage_usage);
+
+        GitSplitsEvent::DistributionScheduleSet {
+            split_id,
+            token,
+            amount,
+            interval,
+            next_run_at: schedule.next_run_at,
+        }
+        .emit();
+    }
+
+    /// Removes the split's schedule for `token`. Funds stay in the treasury. Only the split owner
+    /// can call this.
+    pub fn remove_distribution_schedule(&mut self, split_id: SplitId, token: Option<AccountId>) -> bool {
+        self.assert_split_owner(&split_id);
+        let token = token.map(|token| token.to_string()).unwrap_or_else(|| NEAR_TOKEN.to_string());
+        let initial_storage_usage = self.storage_checkpoint();
+        let removed = match self.distribution_schedules.get_mut(&split_id) {
+            Some(schedules) => {
+                let count = schedules.len();
+                schedules.retain(|schedule| schedule.token != token);
+                schedules.len() < count
+            }
+            None => false,
+        };
+        if removed {
+            let split_owner = env::predecessor_account_id();
+            self.charge_storage(&split_owner, initial_storage_usage);
+            GitSplitsEvent::DistributionScheduleRemoved { split_id, token }.emit();
+        }
+        removed
+    }
+
+    /// Runs every schedule of the split that is due and covered by the treasury. Callable by any
+    /// account; returns the distributions created, which is empty when nothing was due.
+    pub fn execute_due_distribution(&mut self, split_id: SplitId) -> Vec<Distribution> {
+        self.assert_not_paused(PauseArea::Distributions);
+        let now = env::block_timestamp();
+        let Some(schedules) = self.distribution_schedules.get(&split_id).cloned() else {
+            return Vec::new();
+        };
+
+        let mut distributions = Vec::new();
+        for schedule in schedules.iter().filter(|schedule| schedule.next_run_at <= now) {
+            let balance = self.treasury_balance(&split_id, &schedule.token);
+            if balance < schedule.amount.0 {
+                env::log_str(&format!(
+                    "Treasury of {} holds {} of {}, below the scheduled {}",
+                    split_id, balance, schedule.token, schedule.amount.0
+                ));
+                continue;
+            }
+
+            let treasury = self.split_treasuries.get_mut(&split_id).expect("Treasury not found");
+            treasury.insert(schedule.token.clone(), balance - schedule.amount.0);
+            if let Some(stored) = self
+                .distribution_schedules
+                .get_mut(&split_id)
+                .and_then(|schedules| schedules.iter_mut().find(|stored| stored.token == schedule.token))
+            {
+                stored.next_run_at = stored.next_run_at.saturating_add(stored.interval);
+            }
+
+            let treasury_account = env::current_account_id();
+            let distribution =
+                self.internal_distribute(&split_id, &schedule.token, schedule.amount.0, &treasury_account, None);
+            GitSplitsEvent::ScheduledDistributionExecuted {
+                split_id: split_id.clone(),
+                distribution_id: distribution.id.clone(),
+                token: schedule.token.clone(),
+                amount: schedule.amount,
+            }
+            .emit();
+            distributions.push(distribution);
+        }
+        distributions
+    }
+
+    pub fn get_split_treasury(&self, split_id: SplitId) -> Vec<TreasuryBalance> {
+        self.split_treasuries
+            .get(&split_id)
+            .map(|treasury| {
+                treasury
+                    .iter()
+                    .map(|(token, amount)| TreasuryBalance {
+                        token: token.clone(),
+                        amount: U128(*amount),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_distribution_schedules(&self, split_id: SplitId) -> Vec<DistributionSchedule> {
+        self.distribution_schedules.get(&split_id).cloned().unwrap_or_default()
+    }
+}
+
+impl GitSplitsContract {
+    /// Credits `amount` of `token` to a split's treasury and returns the new balance.
+    pub(crate) fn internal_fund_treasury(
+        &mut self,
+        split_id: &SplitId,
+        token: &str,
+        amount: u128,
+        sponsor: &AccountId,
+    ) -> U128 {
+        assert!(self.splits.get(split_id).is_some(), "Split not found");
+        let treasury = self.split_treasuries.entry(split_id.clone()).or_default();
+        let balance = treasury.entry(token.to_string()).or_insert(0);
+        *balance += amount;
+        let balance = U128(*balance);
+
+        GitSplitsEvent::TreasuryFunded {
+            split_id: split_id.clone(),
+            token: token.to_string(),
+            amount: U128(amount),
+            sponsor: sponsor.clone(),
+        }
+        .emit();
+        balance
+    }
+
+    fn treasury_balance(&self, split_id: &SplitId, token: &str) -> u128 {
+        self.split_treasuries
+            .get(split_id)
+            .and_then(|treasury| treasury.get(token))
+            .copied()
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,205 @@
+//! Verified wallets ordered by normalized GitHub username.
+//!
+//! `near_sdk::store` only offers an ordered map behind the `unstable` feature, so the wallets live
+//! in a `LookupMap` and their usernames in a sorted index of buckets holding at most
+//! `BUCKET_CAPACITY` usernames each. The first username, id and size of every bucket are kept
+//! together in one list, so finding a username reads that list and a single bucket, and a page
+//! reads only the buckets it spans. Inserting or removing a username rewrites one bucket, plus the
+//! list when a bucket splits, empties, changes size or changes its first username.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::store::{LazyOption, LookupMap};
+use near_sdk::{AccountId, IntoStorageKey};
+
+/// Most usernames a bucket holds before it is split in two
+const BUCKET_CAPACITY: usize = 128;
+
+/// Position of a bucket in the sorted index
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+struct BucketStart {
+    first_username: String,
+    bucket_id: u32,
+    len: u32,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct VerifiedWallets {
+    wallets: LookupMap<String, AccountId>,
+    bucket_starts: LazyOption<Vec<BucketStart>>,
+    buckets: LookupMap<u32, Vec<String>>,
+    next_bucket_id: u32,
+    len: u64,
+}
+
+impl VerifiedWallets {
+    pub fn new<W, S, B>(wallets_prefix: W, bucket_starts_prefix: S, buckets_prefix: B) -> Self
+    where
+        W: IntoStorageKey,
+        S: IntoStorageKey,
+        B: IntoStorageKey,
+    {
+        Self {
+            wallets: LookupMap::new(wallets_prefix),
+            bucket_starts: LazyOption::new(bucket_starts_prefix, Some(Vec::new())),
+            buckets: LookupMap::new(buckets_prefix),
+            next_bucket_id: 0,
+            len: 0,
+        }
+    }
+
+    pub fn get(&self, github_username: &str) -> Option<&AccountId> {
+        self.wallets.get(github_username)
+    }
+
+    pub fn contains_key(&self, github_username: &str) -> bool {
+        self.wallets.contains_key(github_username)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Sets the wallet of `github_username` and returns the wallet it replaced.
+    pub fn insert(&mut self, github_username: String, wallet_address: AccountId) -> Option<AccountId> {
+        let previous = self.wallets.insert(github_username.clone(), wallet_address);
+        if previous.is_none() {
+            self.index_insert(github_username);
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, github_username: &str) -> Option<AccountId> {
+        let removed = self.wallets.remove(github_username);
+        if removed.is_some() {
+            self.index_remove(github_username);
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Up to `limit` entries in username order, skipping the first `offset`.
+    pub fn page(&self, offset: u64, limit: usize) -> Vec<(String, AccountId)> {
+        let starts = self.starts();
+        let mut skipped: u64 = 0;
+        let mut position = 0;
+        while position < starts.len() && skipped + u64::from(starts[position].len) <= offset {
+            skipped += u64::from(starts[position].len);
+            position += 1;
+        }
+        self.collect_from(starts, position, (offset - skipped) as usize, None, limit)
+    }
+
+    /// Up to `limit` entries in username order whose username sorts after `start_after`.
+    pub fn page_after(&self, start_after: Option<&str>, limit: usize) -> Vec<(String, AccountId)> {
+        let starts = self.starts();
+        let position = start_after.map(|username| bucket_position(starts, username)).unwrap_or(0);
+        self.collect_from(starts, position, 0, start_after, limit)
+    }
+
+    fn collect_from(
+        &self,
+        starts: &[BucketStart],
+        position: usize,
+        skip: usize,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Vec<(String, AccountId)> {
+        starts[position.min(starts.len())..]
+            .iter()
+            .flat_map(|start| self.bucket(start.bucket_id).iter())
+            .skip(skip)
+            .filter(|username| start_after.is_none_or(|after| username.as_str() > after))
+            .take(limit)
+            .filter_map(|username| {
+                self.wallets
+                    .get(username)
+                    .map(|wallet_address| (username.clone(), wallet_address.clone()))
+            })
+            .collect()
+    }
+
+    fn starts(&self) -> &Vec<BucketStart> {
+        self.bucket_starts.get().as_ref().expect("Verification index is missing")
+    }
+
+    fn bucket(&self, bucket_id: u32) -> &Vec<String> {
+        self.buckets.get(&bucket_id).expect("Verification bucket is missing")
+    }
+
+    fn index_insert(&mut self, github_username: String) {
+        let mut starts = self.starts().clone();
+        if starts.is_empty() {
+            let bucket_id = self.allocate_bucket(vec![github_username.clone()]);
+            starts.push(BucketStart {
+                first_username: github_username,
+                bucket_id,
+                len: 1,
+            });
+            self.bucket_starts.set(Some(starts));
+            return;
+        }
+
+        let position = bucket_position(&starts, &github_username);
+        let bucket_id = starts[position].bucket_id;
+        let mut bucket = self.bucket(bucket_id).clone();
+        let index = bucket.binary_search(&github_username).unwrap_or_else(|index| index);
+        bucket.insert(index, github_username);
+
+        if bucket.len() > BUCKET_CAPACITY {
+            let upper = bucket.split_off(bucket.len() / 2);
+            let first_username = upper[0].clone();
+            let len = upper.len() as u32;
+            let upper_id = self.allocate_bucket(upper);
+            starts.insert(position + 1, BucketStart {
+                first_username,
+                bucket_id: upper_id,
+                len,
+            });
+        }
+        starts[position].first_username = bucket[0].clone();
+        starts[position].len = bucket.len() as u32;
+        self.buckets.insert(bucket_id, bucket);
+        self.bucket_starts.set(Some(starts));
+    }
+
+    fn index_remove(&mut self, github_username: &str) {
+        let mut starts = self.starts().clone();
+        let position = bucket_position(&starts, github_username);
+        let Some(start) = starts.get(position) else {
+            return;
+        };
+        let bucket_id = start.bucket_id;
+        let mut bucket = self.bucket(bucket_id).clone();
+        let Ok(index) = bucket.binary_search_by(|username| username.as_str().cmp(github_username)) else {
+            return;
+        };
+        bucket.remove(index);
+
+        if bucket.is_empty() {
+            self.buckets.remove(&bucket_id);
+            starts.remove(position);
+        } else {
+            starts[position].first_username = bucket[0].clone();
+            starts[position].len = bucket.len() as u32;
+            self.buckets.insert(bucket_id, bucket);
+        }
+        self.bucket_starts.set(Some(starts));
+    }
+
+    fn allocate_bucket(&mut self, usernames: Vec<String>) -> u32 {
+        let bucket_id = self.next_bucket_id;
+        self.next_bucket_id += 1;
+        self.buckets.insert(bucket_id, usernames);
+        bucket_id
+    }
+}
+
+/// Position of the bucket that holds, or would hold, `github_username`.
+fn bucket_position(starts: &[BucketStart], github_username: &str) -> usize {
+    starts
+        .partition_point(|start| start.first_username.as_str() <= github_username)
+        .saturating_sub(1)
+}
@@ -0,0 +1,344 @@
+//! Vesting distributions.
+//!
+//! A distribution created with a `VestingSchedule` does not pay contributors right away. Each
+//! contributor's share is held by the contract as a grant that accrues linearly from `start` to
+//! `start + duration`, with nothing withdrawable before `start + cliff`. The contributor's verified
+//! wallet withdraws whatever has accrued with `withdraw_vested`.
+//!
+//! The grantee's transactions in the distribution follow the payouts: `Submitted` while a payout is
+//! in flight, `Completed` once the grant is paid in full, and `Failed` when a transfer fails. A
+//! failed payout is returned to the grant and queues the distribution for `retry_failed`.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::Vector;
+use near_sdk::{env, near_bindgen, AccountId, PromiseError};
+use schemars::JsonSchema;
+use std::collections::BTreeSet;
+
+use crate::events::GitSplitsEvent;
+use crate::{
+    allocation, normalize_github_username, DistributionId, GitSplitsContract, GitSplitsContractExt, PauseArea,
+    StorageKey, TransactionStatus, GAS_FOR_PAYOUT_CALLBACK, NEAR_TOKEN,
+};
+
+/// Linear vesting schedule. Timestamps and durations are in nanoseconds.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingSchedule {
+    /// Time from which shares start to accrue
+    pub start: u64,
+    /// Time after `start` before anything can be withdrawn
+    pub cliff: u64,
+    /// Time after `start` at which shares are fully vested
+    pub duration: u64,
+}
+
+impl VestingSchedule {
+    pub fn assert_valid(&self) {
+        assert!(self.duration > 0, "Vesting duration must be greater than 0");
+        assert!(self.cliff <= self.duration, "Vesting cliff cannot exceed the duration");
+        assert!(self.start.checked_add(self.duration).is_some(), "Vesting schedule overflows");
+    }
+
+    /// Portion of `total` vested at `now`.
+    pub fn vested_amount(&self, total: u128, now: u64) -> u128 {
+        if now < self.start.saturating_add(self.cliff) {
+            0
+        } else if now >= self.start.saturating_add(self.duration) {
+            total
+        } else {
+            allocation::mul_div_rem(total, u128::from(now - self.start), u128::from(self.duration)).0
+        }
+    }
+}
+
+/// A contributor's share of a vesting distribution
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct VestingGrant {
+    pub github_username: String,
+    pub amount: u128,
+    pub withdrawn: u128,
+}
+
+/// Vested and locked amounts of one vesting distribution for a GitHub user
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingBalance {
+    pub distribution_id: DistributionId,
+    pub token: String,
+    pub total: U128,
+    pub vested: U128,
+    pub withdrawn: U128,
+    pub locked: U128,
+}
+
+#[near_bindgen]
+impl GitSplitsContract {
+    /// Pays the caller everything vested and not yet withdrawn from their grants in a vesting
+    /// distribution. The caller must be the verified wallet of the grant's GitHub user.
+    pub fn withdraw_vested(&mut self, distribution_id: DistributionId) -> U128 {
+        self.assert_not_paused(PauseArea::Claims);
+        let wallet_address = env::predecessor_account_id();
+        let github_username = self
+            .wallet_to_github
+            .get(&wallet_address)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("Caller is not a verified wallet"));
+        assert_eq!(
+            self.verified_wallets.get(&github_username),
+            Some(&wallet_address),
+            "Caller is not the verified wallet of {}",
+            github_username
+        );
+
+        let amount = self.internal_pay_vested(&distribution_id, &github_username, wallet_address);
+        assert!(amount > 0, "Nothing to withdraw");
+        U128(amount)
+    }
+
+    /// Re-attempts the failed payouts of a vesting distribution, paying each grantee whose payout
+    /// failed what has vested to their current verified wallet. Callable by any account; returns
+    /// the number of grantees paid again. Grantees that are no longer verified keep their grants and
+    /// withdraw them with `withdraw_vested` once they verify again.
+    pub fn retry_failed(&mut self, distribution_id: DistributionId) -> u64 {
+        self.assert_not_paused(PauseArea::Claims);
+        assert!(
+            self.failed_distributions.remove(&distribution_id),
+            "Distribution has no failed payouts"
+        );
+        let distribution = self.distributions.get(&distribution_id).expect("Distribution not found");
+        let github_usernames: BTreeSet<String> = distribution
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.status == TransactionStatus::Failed)
+            .map(|transaction| transaction.github_username.clone())
+            .collect();
+
+        let mut retried: u64 = 0;
+        let mut transaction_indices = Vec::new();
+        for github_username in github_usernames.iter() {
+            let Some(wallet_address) = self.verified_wallets.get(github_username).cloned() else {
+                continue;
+            };
+            if self.internal_pay_vested(&distribution_id, github_username, wallet_address) > 0 {
+                retried += 1;
+                transaction_indices.extend(self.vesting_transaction_indices(&distribution_id, github_username));
+            }
+        }
+        transaction_indices.sort_unstable();
+        GitSplitsEvent::FailedPayoutsRetried {
+            distribution_id,
+            transaction_indices,
+        }
+        .emit();
+        retried
+    }
+
+    /// Lists the vesting distributions with failed payouts waiting for `retry_failed`.
+    pub fn get_failed_distributions(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<DistributionId> {
+        let start = from_index.unwrap_or(0) as usize;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        self.failed_distributions.iter().skip(start).take(page_size).cloned().collect()
+    }
+
+    /// Settles a vested payout. A failed transfer returns the amount to the grants, marks the
+    /// grantee's transactions failed and queues the distribution for `retry_failed`.
+    #[private]
+    pub fn on_vested_withdrawn(
+        &mut self,
+        distribution_id: DistributionId,
+        github_username: String,
+        wallet_address: AccountId,
+        token: String,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        let succeeded = result.is_ok();
+        if succeeded {
+            let paid_in_full = self.vesting_grants.get(&distribution_id).is_some_and(|grants| {
+                grants
+                    .iter()
+                    .filter(|grant| grant.github_username == github_username)
+                    .all(|grant| grant.withdrawn == grant.amount)
+            });
+            let status = if paid_in_full { TransactionStatus::Completed } else { TransactionStatus::Pending };
+            self.set_vesting_transactions_status(&distribution_id, &github_username, status, None);
+            GitSplitsEvent::VestedWithdrawn {
+                distribution_id,
+                github_username,
+                wallet_address,
+                amount,
+                token,
+            }
+            .emit();
+        } else {
+            let mut unpaid = amount.0;
+            if let Some(grants) = self.vesting_grants.get_mut(&distribution_id) {
+                for grant in grants.iter_mut().filter(|grant| grant.github_username == github_username) {
+                    let restored = unpaid.min(grant.withdrawn);
+                    grant.withdrawn -= restored;
+                    unpaid -= restored;
+                }
+            }
+            let failure_reason = format!("Transfer to {} failed", wallet_address);
+            self.set_vesting_transactions_status(
+                &distribution_id,
+                &github_username,
+                TransactionStatus::Failed,
+                Some(failure_reason),
+            );
+            self.failed_distributions.insert(distribution_id.clone());
+            GitSplitsEvent::VestedWithdrawFailed {
+                distribution_id,
+                github_username,
+                wallet_address,
+                amount,
+                token,
+            }
+            .emit();
+        }
+        succeeded
+    }
+
+    /// Lists the vesting distributions of a GitHub user with their vested and locked amounts.
+    pub fn get_vesting_balances(
+        &self,
+        github_username: String,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<VestingBalance> {
+        let github_username = normalize_github_username(&github_username);
+        let start = from_index.unwrap_or(0) as usize;
+        let page_size = limit.unwrap_or(50).min(200) as usize;
+        self.vesting_balances(&github_username).skip(start).take(page_size).collect()
+    }
+}
+
+impl GitSplitsContract {
+    /// Pays a grantee everything vested and not yet withdrawn from their grants in a vesting
+    /// distribution and returns the amount paid. Nothing is transferred when nothing is available.
+    fn internal_pay_vested(
+        &mut self,
+        distribution_id: &DistributionId,
+        github_username: &str,
+        wallet_address: AccountId,
+    ) -> u128 {
+        let distribution = self.distributions.get(distribution_id).expect("Distribution not found");
+        let schedule = distribution.vesting.clone().expect("Distribution does not vest");
+        let token = distribution.token_id.clone().unwrap_or_else(|| NEAR_TOKEN.to_string());
+        let now = env::block_timestamp();
+
+        let grants = self.vesting_grants.get_mut(distribution_id).expect("Distribution has no vesting grants");
+        let mut amount: u128 = 0;
+        for grant in grants.iter_mut().filter(|grant| grant.github_username == github_username) {
+            let available = schedule.vested_amount(grant.amount, now) - grant.withdrawn;
+            grant.withdrawn += available;
+            amount += available;
+        }
+        if amount == 0 {
+            return 0;
+        }
+
+        self.set_vesting_transactions_status(distribution_id, github_username, TransactionStatus::Submitted, None);
+        let _ = self.internal_transfer(&token, wallet_address.clone(), amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_PAYOUT_CALLBACK)
+                .on_vested_withdrawn(
+                    distribution_id.clone(),
+                    github_username.to_string(),
+                    wallet_address,
+                    token,
+                    U128(amount),
+                ),
+        );
+        amount
+    }
+
+    /// Indices of a grantee's transactions in a vesting distribution.
+    fn vesting_transaction_indices(&self, distribution_id: &DistributionId, github_username: &str) -> Vec<u32> {
+        self.distributions
+            .get(distribution_id)
+            .map(|distribution| {
+                distribution
+                    .transactions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, transaction)| transaction.github_username == github_username)
+                    .map(|(index, _)| index as u32)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_vesting_transactions_status(
+        &mut self,
+        distribution_id: &DistributionId,
+        github_username: &str,
+        status: TransactionStatus,
+        failure_reason: Option<String>,
+    ) {
+        let Some(distribution) = self.distributions.get_mut(distribution_id) else {
+            return;
+        };
+        for transaction in distribution
+            .transactions
+            .iter_mut()
+            .filter(|transaction| transaction.github_username == github_username)
+        {
+            transaction.status = status;
+            transaction.failure_reason = failure_reason.clone();
+        }
+    }
+
+    /// Vested and locked amounts of every vesting distribution of a normalized GitHub username.
+    pub(crate) fn vesting_balances<'a>(&'a self, github_username: &'a str) -> impl Iterator<Item = VestingBalance> + 'a {
+        let now = env::block_timestamp();
+        self.github_vesting
+            .get(github_username)
+            .into_iter()
+            .flat_map(|distribution_ids| distribution_ids.iter())
+            .filter_map(move |distribution_id| {
+                let distribution = self.distributions.get(distribution_id)?;
+                let schedule = distribution.vesting.as_ref()?;
+                let (total, withdrawn) = self
+                    .vesting_grants
+                    .get(distribution_id)?
+                    .iter()
+                    .filter(|grant| grant.github_username == github_username)
+                    .fold((0u128, 0u128), |(total, withdrawn), grant| {
+                        (total + grant.amount, withdrawn + grant.withdrawn)
+                    });
+                let vested = schedule.vested_amount(total, now);
+                Some(VestingBalance {
+                    distribution_id: distribution_id.clone(),
+                    token: distribution.token_id.clone().unwrap_or_else(|| NEAR_TOKEN.to_string()),
+                    total: U128(total),
+                    vested: U128(vested),
+                    withdrawn: U128(withdrawn),
+                    locked: U128(total - vested),
+                })
+            })
+    }
+
+    /// Stores the grants of a new vesting distribution and indexes it under each grantee.
+    pub(crate) fn internal_store_vesting_grants(&mut self, distribution_id: &DistributionId, grants: Vec<VestingGrant>) {
+        for grant in grants.iter() {
+            let distribution_ids = self
+                .github_vesting
+                .entry(grant.github_username.clone())
+                .or_insert_with(|| {
+                    Vector::new(StorageKey::GithubVestingInner {
+                        github_username: grant.github_username.clone(),
+                    })
+                });
+            if distribution_ids.is_empty() || distribution_ids[distribution_ids.len() - 1] != *distribution_id {
+                distribution_ids.push(distribution_id.clone());
+            }
+        }
+        self.vesting_grants.insert(distribution_id.clone(), grants);
+    }
+}
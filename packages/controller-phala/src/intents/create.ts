@@ -202,6 +202,12 @@ export const createIntent: Intent = {
         .filter((_: string, i: number) => !wallets[i]);
       const skippedBots = contributors.length - eligible.length;
 
+      const approvalLine = split.approvalHash
+        ? `⏳ The split owner must approve this allocation before it takes effect:\n` +
+          `near call ${process.env.NEAR_CONTRACT_ID} approve_split_update ` +
+          `'{"split_id": "${split.id}", "contributors_hash": "${split.approvalHash}"}' --accountId <owner>\n` +
+          `Then run this command again.\n\n`
+        : '';
       const coverageLine =
         `Verification coverage: ${verifiedCount}/${eligible.length} verified` +
         (skippedBots > 0 ? ` (${skippedBots} bot/system skipped)` : '');
@@ -221,6 +227,7 @@ export const createIntent: Intent = {
           `Top contributors (verified via Git history):\n${topContributors}` +
           `${contributors.length > 5 ? `\n...and ${contributors.length - 5} more` : ''}\n\n` +
           `${coverageLine}${unverifiedLine}\n\n` +
+          `${approvalLine}` +
           `To pay them: "@gitsplits pay 100 USDC to ${repoUrl}"` +
           `${existing && existing.id ? `\n\nThis split was refreshed with the latest contributors.` : ''}`,
         context: {
@@ -120,24 +120,6 @@ export const payIntent: Intent = {
         percentage: verifiedPercentage > 0 ? (Number(c.percentage) / verifiedPercentage) * 100 : 0,
       }));
 
-      const pendingClaims: Array<{ github_username: string; amount: number; token: string; id: string }> = [];
-      if (unverified.length > 0) {
-        for (const contributor of unverified) {
-          const pendingAmount = (amount * Number(contributor.percentage)) / 100;
-          const pendingId = await tools.near.storePendingDistribution({
-            githubUsername: contributor.github_username,
-            amount: pendingAmount,
-            token,
-          });
-          pendingClaims.push({
-            github_username: contributor.github_username,
-            amount: pendingAmount,
-            token,
-            id: pendingId,
-          });
-        }
-      }
-
       const safetyAlerts = inspectDistributionRisk(
         contributors.map((c: any) => ({
           github_username: c.github_username,
@@ -169,6 +151,24 @@ export const payIntent: Intent = {
         tools
       );
 
+      // Escrow the unverified contributors' shares only once the payout has gone through, so a
+      // blocked or retried command does not escrow them again.
+      const pendingClaims: Array<{ github_username: string; amount: number; token: string; id: string | null }> = [];
+      for (const contributor of unverified) {
+        const pendingAmount = (amount * Number(contributor.percentage)) / 100;
+        const pendingId = await tools.near.storePendingDistribution({
+          githubUsername: contributor.github_username,
+          amount: pendingAmount,
+          token,
+        });
+        pendingClaims.push({
+          github_username: contributor.github_username,
+          amount: pendingAmount,
+          token,
+          id: pendingId,
+        });
+      }
+
       const providerName =
         distribution.engine === 'pingpay'
           ? 'Ping Pay'
@@ -180,7 +180,9 @@ export const payIntent: Intent = {
         pendingClaims.length > 0
           ? `\n\n⏳ Pending claims for unverified contributors (${pendingClaims.length}):\n` +
             pendingClaims
-              .map((c) => `- ${c.github_username}: ${c.amount.toFixed(4)} ${c.token} (claim id: ${c.id})`)
+              .map(
+                (c) => `- ${c.github_username}: ${c.amount.toFixed(4)} ${c.token}` + (c.id ? ` (claim id: ${c.id})` : '')
+              )
               .join('\n') +
             `\n\nInvite them to verify: ${verifyBaseUrl}`
           : '';
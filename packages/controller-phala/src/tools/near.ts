@@ -5,7 +5,7 @@
  * Falls back to mock mode if contract not deployed.
  */
 
-import { connect, keyStores, KeyPair, Contract } from 'near-api-js';
+import { connect, keyStores, KeyPair, Contract, providers, utils } from 'near-api-js';
 
 let contract: any = null;
 let nearAccount: any = null;
@@ -13,6 +13,7 @@ let nearContractId: string | null = null;
 let useMockMode = false;
 const isProductionMode = process.env.AGENT_MODE === 'production';
 let workerRegistrationChecked = false;
+let storageRegistrationChecked = false;
 
 async function initNear() {
   if (contract || useMockMode) return;
@@ -67,6 +68,9 @@ async function initNear() {
         'is_github_verified',
         'get_wallet_address',
         'get_pending_distributions',
+        'storage_balance_of',
+        'hash_split_contributors',
+        'get_split_update_approval',
       ],
       changeMethods: [
         'register_worker',
@@ -74,6 +78,7 @@ async function initNear() {
         'update_split',
         'store_verification',
         'store_pending_distribution',
+        'storage_deposit',
       ],
       useLocalViewExecution: false,
     });
@@ -143,7 +148,7 @@ async function ensureWorkerRegistered() {
   };
 
   await contract.register_worker({
-    _attestation: attestation,
+    attestation,
     code_hash: workerCodeHash,
   });
 
@@ -195,6 +200,7 @@ export const nearTool = {
         id: `split-${Date.now()}`,
         repoUrl: params.repoUrl,
         contributors: params.contributors,
+        approvalHash: null,
       };
     }
 
@@ -204,17 +210,19 @@ export const nearTool = {
       percentage: (BigInt(c.percentage) * BigInt(10) ** BigInt(21)).toString(),
     }));
 
+    await ensureStorageRegistered();
     const splitId = await contract.create_split({
       repo_url: params.repoUrl,
       owner: params.owner,
     });
 
-    await callUpdateSplitRaw(splitId, formattedContributors);
+    const approvalHash = await applyApprovedSplitUpdate(splitId, formattedContributors);
 
     return {
       id: splitId,
       repoUrl: params.repoUrl,
       contributors: normalized,
+      approvalHash,
     };
   },
 
@@ -229,6 +237,7 @@ export const nearTool = {
       return {
         id: params.splitId,
         contributors: params.contributors,
+        approvalHash: null,
         mock: true,
       };
     }
@@ -239,15 +248,12 @@ export const nearTool = {
       percentage: (BigInt(c.percentage) * BigInt(10) ** BigInt(21)).toString(),
     }));
 
-    const ok = await callUpdateSplitRaw(params.splitId, formattedContributors);
-
-    if (!ok) {
-      throw new Error(`Failed to update split ${params.splitId}`);
-    }
+    const approvalHash = await applyApprovedSplitUpdate(params.splitId, formattedContributors);
 
     return {
       id: params.splitId,
       contributors: normalized,
+      approvalHash,
     };
   },
 
@@ -306,8 +312,8 @@ export const nearTool = {
       return `pending-${params.githubUsername}-${Date.now()}`;
     }
 
-    const microAmount = BigInt(Math.round(params.amount * 1_000_000)).toString();
-    return await callStorePendingDistributionRaw(params.githubUsername, microAmount, params.token);
+    await ensureStorageRegistered();
+    return await callStorePendingDistributionRaw(params.githubUsername, params.amount, params.token);
   },
 
   async storeVerification(params: {
@@ -355,6 +361,27 @@ function normalizeStoredPercentage(value: string | number): number {
   return numeric / 1e21;
 }
 
+/**
+ * Applies an allocation the split owner has approved with `approve_split_update`. Returns null once
+ * applied, or the contributors hash the owner still has to approve.
+ */
+async function applyApprovedSplitUpdate(
+  splitId: string,
+  contributors: Array<{ github_username: string; percentage: string }>
+): Promise<string | null> {
+  const contributorsHash = await contract.hash_split_contributors({ contributors });
+  const approvedHash = await contract.get_split_update_approval({ split_id: splitId });
+  if (approvedHash !== contributorsHash) {
+    return contributorsHash;
+  }
+
+  const ok = await callUpdateSplitRaw(splitId, contributors);
+  if (!ok) {
+    throw new Error(`Failed to update split ${splitId}`);
+  }
+  return null;
+}
+
 async function callUpdateSplitRaw(
   splitId: string,
   contributors: Array<{ github_username: string; percentage: string }>
@@ -378,25 +405,102 @@ async function callUpdateSplitRaw(
   return true;
 }
 
+async function ensureStorageRegistered() {
+  if (storageRegistrationChecked || !contract) return;
+
+  const accountId = process.env.NEAR_ACCOUNT_ID;
+  const balance = await contract.storage_balance_of({ account_id: accountId });
+  if (!balance) {
+    const deposit = utils.format.parseNearAmount(process.env.NEAR_STORAGE_DEPOSIT || '0.1');
+    await contract.storage_deposit({
+      args: { account_id: accountId },
+      gas: '30000000000000',
+      amount: deposit,
+    });
+    console.log('[NEAR] Registered storage for', accountId);
+  }
+  storageRegistrationChecked = true;
+}
+
+/**
+ * Escrows a contributor's share on the contract. NEAR is attached as the deposit of
+ * `store_pending_distribution`; any other token is sent to the contract with `ft_transfer_call`
+ * and an `escrow` message, using the token contract and decimals configured in
+ * `NEAR_FT_<TOKEN>_CONTRACT` / `NEAR_FT_<TOKEN>_DECIMALS`. Token escrows return the id from the
+ * `pending_distribution_stored` event, or null when the contract emitted none.
+ */
 async function callStorePendingDistributionRaw(
   githubUsername: string,
-  amount: string,
+  amount: number,
   token: string
-): Promise<string> {
+): Promise<string | null> {
   if (!nearAccount || !nearContractId) {
     throw new Error('[NEAR] Account not initialized for raw contract call');
   }
 
-  const args = `{"github_username":"${escapeJson(githubUsername)}","amount":${amount},"token":"${escapeJson(token)}"}`;
+  if (token.toUpperCase() === 'NEAR') {
+    const yoctoAmount = toBaseUnits(amount, 24);
+    const args = `{"github_username":"${escapeJson(githubUsername)}","amount":${yoctoAmount},"token":"NEAR"}`;
 
-  await nearAccount.functionCall({
-    contractId: nearContractId,
-    methodName: 'store_pending_distribution',
-    args: Buffer.from(args),
+    const outcome = await nearAccount.functionCall({
+      contractId: nearContractId,
+      methodName: 'store_pending_distribution',
+      args: Buffer.from(args),
+      gas: '300000000000000',
+      attachedDeposit: BigInt(yoctoAmount),
+    });
+
+    return providers.getTransactionLastResult(outcome);
+  }
+
+  const fungibleToken = resolveFungibleToken(token);
+  const outcome = await nearAccount.functionCall({
+    contractId: fungibleToken.contractId,
+    methodName: 'ft_transfer_call',
+    args: {
+      receiver_id: nearContractId,
+      amount: toBaseUnits(amount, fungibleToken.decimals),
+      msg: JSON.stringify({ escrow: { github_username: githubUsername } }),
+    },
     gas: '300000000000000',
+    attachedDeposit: BigInt(1),
   });
 
-  return `pending-${githubUsername}-${Date.now()}`;
+  return findPendingDistributionId(outcome);
+}
+
+/** Reads the id of the pending distribution stored by a transaction from its event logs. */
+function findPendingDistributionId(outcome: any): string | null {
+  const logs: string[] = (outcome?.receipts_outcome || []).flatMap((receipt: any) => receipt.outcome?.logs || []);
+  for (const log of logs) {
+    if (!log.startsWith('EVENT_JSON:')) continue;
+    try {
+      const event = JSON.parse(log.slice('EVENT_JSON:'.length));
+      if (event.standard !== 'gitsplits' || event.event !== 'pending_distribution_stored') continue;
+      const data = Array.isArray(event.data) ? event.data[0] : event.data;
+      if (data?.id) return String(data.id);
+    } catch {
+      // Logs of other contracts are not necessarily valid JSON.
+    }
+  }
+  return null;
+}
+
+function resolveFungibleToken(token: string): { contractId: string; decimals: number } {
+  const key = token.toUpperCase().replace(/[^A-Z0-9]/g, '_');
+  const contractId = process.env[`NEAR_FT_${key}_CONTRACT`];
+  const decimals = Number(process.env[`NEAR_FT_${key}_DECIMALS`]);
+  if (!contractId || !Number.isInteger(decimals) || decimals < 0) {
+    throw new Error(
+      `[NEAR] Token ${token} is not configured; set NEAR_FT_${key}_CONTRACT and NEAR_FT_${key}_DECIMALS`
+    );
+  }
+  return { contractId, decimals };
+}
+
+function toBaseUnits(amount: number, decimals: number): string {
+  const [whole, fraction = ''] = amount.toFixed(Math.min(decimals, 6)).split('.');
+  return (BigInt(whole + fraction) * BigInt(10) ** BigInt(decimals - fraction.length)).toString();
 }
 
 function escapeJson(input: string): string {
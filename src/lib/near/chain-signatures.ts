@@ -9,36 +9,52 @@ export enum ChainType {
   BITCOIN = 'bitcoin',
 }
 
+// Fee forwarded to the MPC signer with each signature request (1 yoctoNEAR unless configured)
+const SIGNATURE_DEPOSIT = process.env.NEAR_SIGNATURE_DEPOSIT || '1';
+
 // Transaction types
 export interface Transaction {
+  // Distribution and transaction index the contract recorded for this payout
+  distributionId: string;
+  transactionIndex: number;
   chainId: ChainType;
   recipient: string;
   amount: string;
   data?: string;
 }
 
-// Chain signature response
+// Chain signature response, as persisted on the distribution transaction
 export interface ChainSignature {
-  signature: string;
-  publicKey: string;
-  chainId: string;
+  big_r: string;
+  s: string;
+  recovery_id: number;
+  derivation_path: string;
+  chain_id: string;
 }
 
 /**
  * Generate a chain signature for a transaction
- * In a real implementation, this would call the NEAR contract to generate a signature
- * using NEAR's chain signatures feature
+ * Requests an MPC signature over the transaction hash for a transaction of a recorded
+ * distribution; the contract stores the signature on that transaction.
  */
 export async function generateChainSignature(transaction: Transaction): Promise<ChainSignature> {
   try {
-    // Prepare transaction data
-    const txData = JSON.stringify(transaction);
+    // The signer signs the 32-byte hash of the target-chain transaction
+    const payload = crypto.createHash('sha256')
+      .update(JSON.stringify({
+        chainId: transaction.chainId,
+        recipient: transaction.recipient,
+        amount: transaction.amount,
+        data: transaction.data,
+      }))
+      .digest();
     
     // Call the contract to generate a chain signature
     const signature = await callChangeMethod('generate_chain_signature', {
-      chain_id: transaction.chainId,
-      tx_data: txData,
-    });
+      distribution_id: transaction.distributionId,
+      transaction_index: transaction.transactionIndex,
+      payload: Array.from(payload),
+    }, '300000000000000', SIGNATURE_DEPOSIT);
     
     return signature;
   } catch (error) {
@@ -75,7 +75,12 @@ export async function callViewMethod<T>(methodName: string, args: any = {}): Pro
 /**
  * Call a change method on the contract
  */
-export async function callChangeMethod(methodName: string, args: any = {}, gas: string = '300000000000000'): Promise<any> {
+export async function callChangeMethod(
+  methodName: string,
+  args: any = {},
+  gas: string = '300000000000000',
+  deposit: string = '0'
+): Promise<any> {
   const { account, contractId } = await connectToNear();
 
   const result = await account.functionCall({
@@ -83,7 +88,7 @@ export async function callChangeMethod(methodName: string, args: any = {}, gas:
     methodName,
     args,
     gas: BigInt(gas),
-    attachedDeposit: BigInt(0),
+    attachedDeposit: BigInt(deposit),
   }) as FinalExecutionOutcome;
 
   // Parse the result - check if status is a SuccessValue type
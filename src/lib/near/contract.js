@@ -184,14 +184,24 @@ export class GitSplitsContract {
   }
 
   /**
-   * Generate a chain signature
-   * @param {string} chainId - The chain ID
-   * @param {string} txData - The transaction data
+   * Generate a chain signature for a transaction of a recorded distribution
+   * @param {string} distributionId - The distribution ID
+   * @param {number} transactionIndex - Index of the transaction in the distribution
+   * @param {number[]} payload - The 32-byte hash of the target-chain transaction
+   * @param {string} deposit - Fee forwarded to the MPC signer, in yoctoNEAR
    * @returns {Promise<Object>}
    */
-  async generateChainSignature(chainId, txData) {
+  async generateChainSignature(distributionId, transactionIndex, payload, deposit = '1') {
     await this.ensureInitialized();
-    return this.contract.generate_chain_signature({ chain_id: chainId, _tx_data: txData });
+    return this.contract.generate_chain_signature({
+      args: {
+        distribution_id: distributionId,
+        transaction_index: transactionIndex,
+        payload,
+      },
+      gas: '300000000000000',
+      amount: deposit,
+    });
   }
 
   /**
@@ -22,6 +22,7 @@ export interface Contributor {
 export interface Split {
   id: string;
   repo_url: string;
+  repo_key: string | null;
   owner: string;
   contributors: Contributor[];
   created_at: number;
@@ -29,8 +30,10 @@ export interface Split {
 }
 
 export interface ChainSignature {
-  signature: string;
-  public_key: string;
+  big_r: string;
+  s: string;
+  recovery_id: number;
+  derivation_path: string;
   chain_id: string;
 }
 
@@ -203,7 +206,12 @@ export class NearContractService {
     }
   }
 
-  async generateChainSignature(chainId: string, txData: string): Promise<ChainSignature> {
+  async generateChainSignature(
+    distributionId: string,
+    transactionIndex: number,
+    payload: number[],
+    deposit: string = '1'
+  ): Promise<ChainSignature> {
     try {
       const wallet = await this.getWallet();
       
@@ -215,23 +223,19 @@ export class NearContractService {
             params: {
               methodName: 'generate_chain_signature',
               args: {
-                chain_id: chainId,
-                _tx_data: txData,
+                distribution_id: distributionId,
+                transaction_index: transactionIndex,
+                payload,
               },
               gas: GAS_FOR_CALL,
-              deposit: '0',
+              deposit,
             },
           },
         ],
       });
       
-      // In a real implementation, you would parse the result to get the signature
-      // For now, we'll just return a placeholder
-      return {
-        signature: 'signature_placeholder',
-        public_key: 'public_key_placeholder',
-        chain_id: chainId,
-      };
+      // The contract returns the signature it persisted on the transaction
+      return providers.getTransactionLastResult(result);
     } catch (error) {
       console.error('Error generating chain signature:', error);
       throw error;